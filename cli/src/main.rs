@@ -0,0 +1,430 @@
+//! Generate, print, solve and analyze boards from a terminal, no window required.
+
+use std::{collections::BTreeMap, env, fs, hash::Hash, process, str::FromStr, time::Instant};
+
+use hex2d::{Coordinate, Spacing};
+use mofang_engine::{
+    difficulty::{Analyzer, Tier},
+    replay::Replay,
+    solution,
+    solver::legal_combos,
+    unmatch, Board, Mask, Node, Solution, Solver,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+const USAGE: &str = "\
+usage: mofang_cli <command> [options]
+
+commands:
+    generate    generate a board and print it
+    solve       generate a board, print it, and try to clear it
+    stats       generate a bunch of boards and report how the solver does on them, and how hard they are
+    verify <file>
+                check that a replay is a legal clear of --seed, and print its score
+
+options:
+    --game <mofang|sigmar>  which game to generate for (default mofang)
+    --seed <n>              seed to generate from (default random)
+    --count <n>             how many boards `stats` looks at, counting up from --seed (default 10)
+    --diameter <n>          how many hexes across the board is (default depends on the game)
+    --layout <file>         use the board shape drawn in this file instead of a hexagon
+    --rules <words>         play a variant of the rules, like \"qi_matches_elements=true freeness=2\"
+                            (the same words as the `rules` line in a save)
+    --solvable              only generate boards the solver has proven can be cleared
+    --unmatch               generate boards by playing the game backwards, so they can always be cleared
+    --tier <easy|normal|hard>
+                            only generate boards rated this hard (implies --solvable unless --unmatch)
+    --max-states <n>        how many board states the solver looks at before giving up
+    --max-repairs <n>       how many leftovers --solvable is allowed to take off the board
+    --max-attempts <n>      how many boards --solvable tries before giving up on a seed
+    --export <file>         have `solve` write the solution to this file, combo by combo
+";
+
+enum Command {
+    Generate,
+    Solve,
+    Stats,
+    Verify(String),
+}
+
+struct Args {
+    command: Command,
+    game: String,
+    seed: u64,
+    count: u64,
+    diameter: Option<i32>,
+    layout: Option<Mask>,
+    /// Checked once we know which game they're for.
+    rules: Option<String>,
+    solvable: bool,
+    unmatch: bool,
+    tier: Option<Tier>,
+    solver: Solver,
+    export: Option<String>,
+}
+
+fn main() {
+    let raw: Vec<_> = env::args().skip(1).collect();
+    if raw.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let args = match parse_args(raw.into_iter()) {
+        Ok(it) => it,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.game == MofangNode::name() {
+        run::<MofangNode>(&args);
+    } else if args.game == SigmarNode::name() {
+        run::<SigmarNode>(&args);
+    } else {
+        eprintln!("unknown game `{}`\n\n{}", args.game, USAGE);
+        process::exit(2);
+    }
+}
+
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = match raw.next().as_deref() {
+        Some("generate") => Command::Generate,
+        Some("solve") => Command::Solve,
+        Some("stats") => Command::Stats,
+        Some("verify") => Command::Verify(
+            raw.next()
+                .ok_or_else(|| "`verify` needs a replay file".to_owned())?,
+        ),
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+        None => return Err("no command given".to_owned()),
+    };
+    let mut args = Args {
+        command,
+        game: MofangNode::name().to_owned(),
+        seed: fastrand::u64(..),
+        count: 10,
+        diameter: None,
+        layout: None,
+        rules: None,
+        solvable: false,
+        unmatch: false,
+        tier: None,
+        solver: Solver::default(),
+        export: None,
+    };
+
+    let mut seeded = false;
+    while let Some(flag) = raw.next() {
+        seeded |= flag == "--seed";
+        match flag.as_str() {
+            "--solvable" => args.solvable = true,
+            "--unmatch" => args.unmatch = true,
+            "--tier" => args.tier = Some(value(&flag, raw.next())?),
+            "--game" => args.game = value(&flag, raw.next())?,
+            "--seed" => args.seed = value(&flag, raw.next())?,
+            "--count" => args.count = value(&flag, raw.next())?,
+            "--diameter" => args.diameter = Some(value(&flag, raw.next())?),
+            "--layout" => args.layout = Some(layout(&value::<String>(&flag, raw.next())?)?),
+            "--rules" => args.rules = Some(value(&flag, raw.next())?),
+            "--max-states" => args.solver.max_states = value(&flag, raw.next())?,
+            "--max-repairs" => args.solver.max_repairs = value(&flag, raw.next())?,
+            "--max-attempts" => args.solver.max_attempts = value(&flag, raw.next())?,
+            "--export" => args.export = Some(value(&flag, raw.next())?),
+            _ => return Err(format!("unknown option `{}`", flag)),
+        }
+    }
+    if matches!(args.command, Command::Verify(_)) && !seeded {
+        // A random seed would never match
+        return Err("`verify` needs the --seed the replay should be on".to_owned());
+    }
+    Ok(args)
+}
+
+/// Parse the value that goes with a flag.
+fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("`{}` needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("couldn't understand `{}` for `{}`", value, flag))
+}
+
+/// Read a board shape out of a file.
+fn layout(path: &str) -> Result<Mask, String> {
+    let text =
+        fs::read_to_string(path).map_err(|err| format!("couldn't read `{}`: {}", path, err))?;
+    Mask::parse(&text).map_err(|err| format!("bad layout in `{}`: {}", path, err))
+}
+
+fn run<N: Node + Clone + Hash + PartialEq>(args: &Args) {
+    match args.command {
+        Command::Generate => {
+            let (board, _) = generate::<N>(args, args.seed);
+            println!("{} seed {}", N::name(), args.seed);
+            print_board(&board);
+        }
+        Command::Solve => {
+            let (board, _) = generate::<N>(args, args.seed);
+            println!("{} seed {}", N::name(), args.seed);
+            print_board(&board);
+            println!();
+
+            let start = Instant::now();
+            let solution = args.solver.solve(&board);
+            let elapsed = start.elapsed();
+            match solution {
+                Solution::Solved(line) => {
+                    println!("solved in {} moves ({:.2?})", line.len(), elapsed);
+                    let steps = solution::steps(&board, &line)
+                        .expect("the solver only finds lines that work");
+                    for (idx, mov) in steps.iter().enumerate() {
+                        println!("{:>4}. {}", idx + 1, solution::describe(mov));
+                    }
+                    if let Some(path) = &args.export {
+                        if let Err(err) = fs::write(path, solution::write(&steps)) {
+                            eprintln!("couldn't export to `{}`: {}", path, err);
+                            process::exit(1);
+                        }
+                    }
+                }
+                Solution::Impossible => println!("impossible ({:.2?})", elapsed),
+                Solution::GaveUp => println!("gave up ({:.2?})", elapsed),
+            }
+        }
+        Command::Stats => stats::<N>(args),
+        Command::Verify(ref path) => verify::<N>(args, path),
+    }
+}
+
+/// Check a replay file, exiting with an error if it isn't a clear.
+fn verify<N: Node + Clone + Hash>(args: &Args, path: &str) {
+    let replay = match fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| Replay::read(&text).map_err(|err| err.to_string()))
+    {
+        Ok(it) => it,
+        Err(err) => {
+            eprintln!("couldn't read `{}`: {}", path, err);
+            process::exit(2);
+        }
+    };
+    match replay.verify::<N>(args.seed) {
+        Ok(score) => println!(
+            "valid: {} points, {} combos, {:.3}s",
+            score.points,
+            score.moves,
+            score.elapsed.as_secs_f64()
+        ),
+        Err(err) => {
+            println!("invalid: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Make a board, along with a line that clears it if the way it was made found one.
+fn generate<N: Node + Clone + Hash + PartialEq>(
+    args: &Args,
+    seed: u64,
+) -> (Board<N>, Option<Vec<Vec<Coordinate>>>) {
+    let mut options = N::gen_options();
+    if let Some(diameter) = args.diameter {
+        options.diameter = diameter;
+    }
+    options.mask = args.layout.clone();
+    options.tier = args.tier;
+    if let Some(words) = &args.rules {
+        let words: Vec<_> = words.split_whitespace().collect();
+        options.rules = N::read_rules(&words).unwrap_or_else(|| {
+            eprintln!(
+                "couldn't understand the rules `{}` for {}",
+                words.join(" "),
+                N::name()
+            );
+            process::exit(2);
+        });
+    }
+    if let Err(err) = options.validate() {
+        eprintln!("bad options for {}: {}", N::name(), err);
+        process::exit(2);
+    }
+    if args.unmatch {
+        match unmatch::generate(seed, &options) {
+            Ok((board, line)) => (board, Some(line)),
+            Err(err) => {
+                eprintln!("couldn't unmatch a board from seed {}: {}", seed, err);
+                process::exit(1);
+            }
+        }
+    } else if args.solvable || args.tier.is_some() {
+        match args.solver.solvable_game_with(seed, &options) {
+            Ok((board, line)) => (board, Some(line)),
+            Err(err) => {
+                eprintln!("couldn't make a solvable board from seed {}: {}", seed, err);
+                process::exit(1);
+            }
+        }
+    } else {
+        match N::new_game_with(seed, &options) {
+            Ok(board) => (board, None),
+            Err(err) => {
+                eprintln!("couldn't make a board from seed {}: {}", seed, err);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Run the solver over `count` seeds and print a row for each, then a summary.
+fn stats<N: Node + Clone + Hash + PartialEq>(args: &Args) {
+    println!("seed\tnodes\topening_combos\tresult\tmoves\tgen_ms\tsolve_ms\tscore\ttier\trate_ms");
+
+    let analyzer = Analyzer::default();
+    let (mut solved, mut impossible, mut gave_up) = (0, 0, 0);
+    let (mut total_gen_ms, mut total_solve_ms, mut total_rate_ms) = (0.0, 0.0, 0.0);
+    let mut tiers = BTreeMap::new();
+    for seed in (0..args.count).map(|idx| args.seed.wrapping_add(idx)) {
+        let start = Instant::now();
+        let (board, line) = generate::<N>(args, seed);
+        let gen_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let start = Instant::now();
+        let solution = args.solver.solve(&board);
+        let solve_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let (result, moves) = match &solution {
+            Solution::Solved(line) => {
+                solved += 1;
+                ("solved", line.len().to_string())
+            }
+            Solution::Impossible => {
+                impossible += 1;
+                ("impossible", "-".to_owned())
+            }
+            Solution::GaveUp => {
+                gave_up += 1;
+                ("gave_up", "-".to_owned())
+            }
+        };
+        total_gen_ms += gen_ms;
+        total_solve_ms += solve_ms;
+
+        let start = Instant::now();
+        let difficulty = match &line {
+            Some(line) => analyzer.rate_solved(&board, line),
+            None => analyzer.rate(&board),
+        };
+        let rate_ms = start.elapsed().as_secs_f64() * 1000.0;
+        total_rate_ms += rate_ms;
+        *tiers.entry(difficulty.tier).or_insert(0) += 1;
+
+        let nodes = board.node_count();
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{:.1}\t{:.1}\t{:.1}\t{}\t{:.1}",
+            seed,
+            nodes,
+            legal_combos(&board).len(),
+            result,
+            moves,
+            gen_ms,
+            solve_ms,
+            difficulty.score,
+            difficulty.tier,
+            rate_ms
+        );
+    }
+
+    let count = args.count.max(1) as f64;
+    println!();
+    println!(
+        "{} boards: {} solved, {} impossible, {} gave up",
+        args.count, solved, impossible, gave_up
+    );
+    let tiers: Vec<_> = tiers
+        .iter()
+        .map(|(tier, n)| format!("{} {}", n, tier))
+        .collect();
+    println!("rated {}", tiers.join(", "));
+    println!(
+        "average generation {:.1}ms, average solve {:.1}ms, average rating {:.1}ms",
+        total_gen_ms / count,
+        total_solve_ms / count,
+        total_rate_ms / count
+    );
+}
+
+/// Print the board as hex art, with two letters per node and `.` for empty slots.
+fn print_board<N: Node>(board: &Board<N>) {
+    let half_width = 3f32.sqrt() / 2.0;
+    let cells: Vec<_> = board
+        .nodes_iter()
+        .map(|(coord, node)| {
+            // Columns are half a hex wide and rows are three quarters of a hex tall,
+            // so every slot lands on a whole cell
+            let (px, py) = coord.to_pixel(Spacing::PointyTop(1.0));
+            let col = (px / half_width).round() as i32;
+            let row = (py / 1.5).round() as i32;
+            (row, col, node.map(|n| n.texture_name()))
+        })
+        .collect();
+    let min_row = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let max_row = cells.iter().map(|c| c.0).max().unwrap_or(0);
+    let min_col = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    let max_col = cells.iter().map(|c| c.1).max().unwrap_or(0);
+
+    let mut grid = vec![
+        vec!["  ".to_owned(); (max_col - min_col + 1) as usize];
+        (max_row - min_row + 1) as usize
+    ];
+    let mut legend = BTreeMap::new();
+    for (row, col, name) in cells {
+        let text = match name {
+            Some(name) => {
+                let abbr = abbreviation(name);
+                legend.insert(abbr.clone(), name);
+                abbr
+            }
+            None => " .".to_owned(),
+        };
+        grid[(row - min_row) as usize][(col - min_col) as usize] = text;
+    }
+
+    for row in grid {
+        println!("{}", row.concat().trim_end());
+    }
+    let legend: Vec<_> = legend
+        .iter()
+        .map(|(abbr, name)| format!("{} {}", abbr, name))
+        .collect();
+    println!("{}", legend.join(", "));
+}
+
+/// Two letters to stand for a node, going by its texture name.
+fn abbreviation(name: &str) -> String {
+    let abbr = match name {
+        // mofang
+        "wood" => "Wd",
+        "earthly" => "El",
+        "yin" => "Yi",
+        "yang" => "Ya",
+        // sigmar
+        "quicksilver" => "Qs",
+        "lead" => "Pb",
+        "tin" => "Sn",
+        "iron" => "Fe",
+        "copper" => "Cu",
+        "silver" => "Ag",
+        "gold" => "Au",
+        _ => {
+            // Everything else is just the first two letters
+            let mut chars = name.chars();
+            return chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars.next())
+                .collect();
+        }
+    };
+    abbr.to_owned()
+}
@@ -46,30 +46,79 @@ pub struct Textures {
 
     pub create_base: Texture2D,
     pub destroy_base: Texture2D,
+
+    pub sigmar: SigmarTextures,
+}
+
+/// Sigmar's Garden has its own marbles, so they get their own folder.
+pub struct SigmarTextures {
+    pub salt: Texture2D,
+    pub quintessence: Texture2D,
+    pub water: Texture2D,
+    pub earth: Texture2D,
+    pub fire: Texture2D,
+    pub air: Texture2D,
+
+    pub quicksilver: Texture2D,
+    pub lead: Texture2D,
+    pub tin: Texture2D,
+    pub iron: Texture2D,
+    pub copper: Texture2D,
+    pub silver: Texture2D,
+    pub gold: Texture2D,
+
+    pub vitae: Texture2D,
+    pub mors: Texture2D,
 }
 
 impl Textures {
     async fn init() -> Self {
         Self {
-            wood: texture("wood").await,
-            fire: texture("fire").await,
-            earth: texture("earth").await,
-            metal: texture("metal").await,
-            water: texture("water").await,
-            heavenly: texture("heavenly").await,
-            earthly: texture("earthly").await,
-            human: texture("human").await,
-            yin: texture("yin").await,
-            yang: texture("yang").await,
-            creation: texture("creation").await,
-            destruction: texture("destruction").await,
-            qi: texture("qi").await,
-
-            highlight: texture("highlight").await,
-            select: texture("select").await,
-            hex: texture("hex").await,
-            create_base: texture("create_base").await,
-            destroy_base: texture("destroy_base").await,
+            wood: texture("mofang", "wood").await,
+            fire: texture("mofang", "fire").await,
+            earth: texture("mofang", "earth").await,
+            metal: texture("mofang", "metal").await,
+            water: texture("mofang", "water").await,
+            heavenly: texture("mofang", "heavenly").await,
+            earthly: texture("mofang", "earthly").await,
+            human: texture("mofang", "human").await,
+            yin: texture("mofang", "yin").await,
+            yang: texture("mofang", "yang").await,
+            creation: texture("mofang", "creation").await,
+            destruction: texture("mofang", "destruction").await,
+            qi: texture("mofang", "qi").await,
+
+            highlight: texture("mofang", "highlight").await,
+            select: texture("mofang", "select").await,
+            hex: texture("mofang", "hex").await,
+            create_base: texture("mofang", "create_base").await,
+            destroy_base: texture("mofang", "destroy_base").await,
+
+            sigmar: SigmarTextures::init().await,
+        }
+    }
+}
+
+impl SigmarTextures {
+    async fn init() -> Self {
+        Self {
+            salt: texture("sigmar", "salt").await,
+            quintessence: texture("sigmar", "quintessence").await,
+            water: texture("sigmar", "water").await,
+            earth: texture("sigmar", "earth").await,
+            fire: texture("sigmar", "fire").await,
+            air: texture("sigmar", "air").await,
+
+            quicksilver: texture("sigmar", "quicksilver").await,
+            lead: texture("sigmar", "lead").await,
+            tin: texture("sigmar", "tin").await,
+            iron: texture("sigmar", "iron").await,
+            copper: texture("sigmar", "copper").await,
+            silver: texture("sigmar", "silver").await,
+            gold: texture("sigmar", "gold").await,
+
+            vitae: texture("sigmar", "vitae").await,
+            mors: texture("sigmar", "mors").await,
         }
     }
 }
@@ -87,12 +136,12 @@ static ASSETS_ROOT: Lazy<PathBuf> = Lazy::new(|| {
     }
 });
 
-async fn texture(path: &str) -> Texture2D {
+async fn texture(game: &str, path: &str) -> Texture2D {
     let with_extension = path.to_owned() + ".png";
     load_texture(
         ASSETS_ROOT
             .join("textures")
-            .join("mofang") // TODO modularize thsi
+            .join(game)
             .join(with_extension)
             .to_string_lossy()
             .as_ref(),
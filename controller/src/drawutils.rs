@@ -1,27 +1,19 @@
-use std::f32::consts::TAU;
+use std::{f32::consts::TAU, time::Duration};
 
 use macroquad::prelude::*;
-use mofang_games::MofangNode;
-
-use crate::{Globals, HEX_HEIGHT, NODE_RADIUS};
-
-/// Draw the MofangNode centered at that position.
-pub fn node(globals: &Globals, node: &MofangNode, cx: f32, cy: f32, faded: bool) {
-    let tex = match node {
-        MofangNode::Wood => globals.assets.textures.wood,
-        MofangNode::Fire => globals.assets.textures.fire,
-        MofangNode::Earth => globals.assets.textures.earth,
-        MofangNode::Metal => globals.assets.textures.metal,
-        MofangNode::Water => globals.assets.textures.water,
-        MofangNode::Heavenly => globals.assets.textures.heavenly,
-        MofangNode::Earthly => globals.assets.textures.earthly,
-        MofangNode::Human => globals.assets.textures.human,
-        MofangNode::Yin => globals.assets.textures.yin,
-        MofangNode::Yang => globals.assets.textures.yang,
-        MofangNode::Creation => globals.assets.textures.creation,
-        MofangNode::Destruction => globals.assets.textures.destruction,
-        MofangNode::Qi => globals.assets.textures.qi,
-    };
+use mofang_games::{MofangNode, SigmarNode};
+
+use crate::{playable::Playable, Globals, HEX_HEIGHT, HEX_WIDTH, NODE_RADIUS};
+
+/// Format a time like a clock, as minutes and seconds.
+pub fn clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Draw the node centered at that position.
+pub fn node<N: Playable>(globals: &Globals, node: &N, cx: f32, cy: f32, faded: bool) {
+    let tex = node.texture(&globals.assets.textures);
 
     if faded {
         gl_use_material(globals.assets.fade_shader);
@@ -74,6 +66,19 @@ pub fn center_text(globals: &Globals, text: &str, size: u16, cx: f32, cy: f32) {
     self::text(globals, text, size, cx, center_y, TextAlign::Center);
 }
 
+/// Draw a button with a label in the middle, grayed out if it's disabled.
+pub fn button(globals: &Globals, rect: Rect, label: &str, enabled: bool) {
+    let color = if enabled { BLACK } else { LIGHTGRAY };
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, color);
+    center_text(
+        globals,
+        label,
+        20,
+        rect.x + rect.w / 2.0,
+        rect.y + rect.h / 2.0,
+    );
+}
+
 pub fn arrow(
     position: (f32, f32),
     angle: f32,
@@ -174,3 +179,45 @@ where
         continuation(pos.0, pos.1, (0.25 - idx as f32 * 0.2) * TAU, node.clone());
     }
 }
+
+/// Sigmar's counting UI: the elements, the metals in the order they have to go,
+/// and everything else.
+pub fn sigmar_table<C>(globals: &Globals, x: f32, y: f32, mut continuation: C)
+where
+    C: FnMut(f32, f32, f32, SigmarNode),
+{
+    let rows: [&[SigmarNode]; 4] = [
+        &[
+            SigmarNode::Water,
+            SigmarNode::Earth,
+            SigmarNode::Fire,
+            SigmarNode::Air,
+        ],
+        &[
+            SigmarNode::Salt,
+            SigmarNode::Quintessence,
+            SigmarNode::Vitae,
+            SigmarNode::Mors,
+        ],
+        &[
+            SigmarNode::Quicksilver,
+            SigmarNode::Lead,
+            SigmarNode::Tin,
+            SigmarNode::Iron,
+        ],
+        &[SigmarNode::Copper, SigmarNode::Silver, SigmarNode::Gold],
+    ];
+    let spacing = HEX_WIDTH * 1.1;
+    for (row_idx, row) in rows.iter().enumerate() {
+        let row_y = y + (row_idx as f32 - 1.5) * spacing;
+        let row_left = x - (row.len() - 1) as f32 * spacing * 0.5;
+        for (idx, node) in row.iter().enumerate() {
+            let node_x = row_left + idx as f32 * spacing;
+            self::node(globals, node, node_x, row_y, false);
+            // counters go in the bottom right so they don't hit the next node over
+            continuation(node_x, row_y, -TAU * 0.125, node.clone());
+        }
+    }
+    // Split the metals off from the rest
+    draw_line(x - spacing * 2.0, y, x + spacing * 2.0, y, 1.2, GRAY);
+}
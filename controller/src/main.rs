@@ -1,10 +1,20 @@
 mod assets;
 mod drawutils;
 mod modes;
+mod playable;
+mod saves;
 
 use assets::Assets;
 use macroquad::prelude::*;
-use modes::{game::ModeGame, rules::ModeRules};
+use modes::{
+    calendar::ModeCalendar, finding::ModeFinding, game::ModeGame, picker::ModePicker,
+    playback::ModePlayback, rules::ModeRules, stats::ModeStats, walkthrough::ModeWalkthrough,
+};
+use mofang_engine::{difficulty::Tier, replay::Replay, Node};
+use mofang_games::{MofangNode, SigmarNode};
+use playable::Playable;
+
+use std::env;
 
 const HEX_SIZE: f32 = 40.0;
 const HEX_WIDTH: f32 = HEX_SIZE * 1.732_050_8; // sqrt(3)
@@ -25,18 +35,38 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let mut mode_stack = vec![Mode::Game(ModeGame::new_game())];
+    let args: Vec<_> = env::args().skip(1).collect();
+    let profile = match profile(&args) {
+        Some(it) => it,
+        None => {
+            println!("profile names can't be blank");
+            std::process::exit(2);
+        }
+    };
+    let mut mode_stack = vec![first_mode(&args, &profile)];
     let mut globals = Globals {
         assets: Assets::init().await,
+        profile,
+        tier: None,
     };
 
     loop {
         clear_background(WHITE);
 
         let transition = match mode_stack.last_mut().unwrap() {
-            Mode::Game(game) => game.update(&mut globals),
+            Mode::Picker(picker) => picker.update(&mut globals),
+            Mode::MofangGame(game) => game.update(&mut globals),
+            Mode::SigmarGame(game) => game.update(&mut globals),
+            Mode::MofangPlayback(playback) => playback.update(&mut globals),
+            Mode::SigmarPlayback(playback) => playback.update(&mut globals),
+            Mode::MofangWalkthrough(walkthrough) => walkthrough.update(&mut globals),
+            Mode::SigmarWalkthrough(walkthrough) => walkthrough.update(&mut globals),
+            Mode::MofangFinding(finding) => finding.update(&mut globals),
+            Mode::SigmarFinding(finding) => finding.update(&mut globals),
 
             Mode::Rules(rules) => rules.update(&mut globals),
+            Mode::Stats(stats) => stats.update(&mut globals),
+            Mode::Calendar(calendar) => calendar.update(&mut globals),
         };
         match transition {
             Transition::None => {}
@@ -44,27 +74,125 @@ async fn main() {
             Transition::Pop => {
                 if mode_stack.len() > 1 {
                     mode_stack.pop();
+                } else {
+                    // Like when starting straight in a replay
+                    *mode_stack.last_mut().unwrap() = Mode::Picker(ModePicker);
                 }
             }
             Transition::Swap(m) => *mode_stack.last_mut().unwrap() = m,
         }
 
         match mode_stack.last().unwrap() {
-            Mode::Game(game) => game.draw(&globals),
+            Mode::Picker(picker) => picker.draw(&globals),
+            Mode::MofangGame(game) => game.draw(&globals),
+            Mode::SigmarGame(game) => game.draw(&globals),
+            Mode::MofangPlayback(playback) => playback.draw(&globals),
+            Mode::SigmarPlayback(playback) => playback.draw(&globals),
+            Mode::MofangWalkthrough(walkthrough) => walkthrough.draw(&globals),
+            Mode::SigmarWalkthrough(walkthrough) => walkthrough.draw(&globals),
+            Mode::MofangFinding(finding) => finding.draw(&globals),
+            Mode::SigmarFinding(finding) => finding.draw(&globals),
             Mode::Rules(rules) => rules.draw(&globals),
+            Mode::Stats(stats) => stats.draw(&globals),
+            Mode::Calendar(calendar) => calendar.draw(&globals),
         }
 
         next_frame().await
     }
 }
 
+/// The value after a command-line flag, if it's there.
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|idx| args.get(idx + 1))
+}
+
+/// Start in the picker, straight in a game with `--seed <n> [--game <mofang|sigmar>]`,
+/// or watching a replay with `--replay <file>`.
+fn first_mode(args: &[String], profile: &str) -> Mode {
+    if let Some(path) = flag(args, "--replay") {
+        return match saves::load_replay(path) {
+            Some(replay) if replay.game == SigmarNode::name() => watch::<SigmarNode>(replay),
+            Some(replay) => watch::<MofangNode>(replay),
+            None => Mode::Picker(ModePicker),
+        };
+    }
+
+    let seed = match flag(args, "--seed").map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            println!("couldn't understand the seed, it should be a number");
+            return Mode::Picker(ModePicker);
+        }
+        None => return Mode::Picker(ModePicker),
+    };
+    match flag(args, "--game").map(String::as_str) {
+        None => Mode::MofangFinding(ModeFinding::seeded(profile, seed)),
+        Some(name) if name == MofangNode::name() => {
+            Mode::MofangFinding(ModeFinding::seeded(profile, seed))
+        }
+        Some(name) if name == SigmarNode::name() => {
+            Mode::SigmarFinding(ModeFinding::seeded(profile, seed))
+        }
+        Some(name) => {
+            println!("unknown game `{}`", name);
+            Mode::Picker(ModePicker)
+        }
+    }
+}
+
+fn watch<N: Playable>(replay: Replay) -> Mode {
+    match ModePlayback::new(replay) {
+        Ok(playback) => N::playback(playback),
+        Err(err) => {
+            println!("couldn't watch the replay: {}", err);
+            Mode::Picker(ModePicker)
+        }
+    }
+}
+
+/// Who's playing, from `--profile <name>`, or whoever's logged in.
+/// A `--profile` that's blank is `None`, rather than quietly being someone else.
+fn profile(args: &[String]) -> Option<String> {
+    let name = match flag(args, "--profile") {
+        Some(name) => name.clone(),
+        None => env::var("USER")
+            .ok()
+            .or_else(|| env::var("USERNAME").ok())
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| "player".to_owned()),
+    };
+    // Profiles go in space-separated files and in file names
+    let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
 pub enum Mode {
-    Game(ModeGame),
+    Picker(ModePicker),
+    MofangGame(ModeGame<MofangNode>),
+    SigmarGame(ModeGame<SigmarNode>),
+    MofangPlayback(ModePlayback<MofangNode>),
+    SigmarPlayback(ModePlayback<SigmarNode>),
+    MofangWalkthrough(ModeWalkthrough<MofangNode>),
+    SigmarWalkthrough(ModeWalkthrough<SigmarNode>),
+    MofangFinding(ModeFinding<MofangNode>),
+    SigmarFinding(ModeFinding<SigmarNode>),
     Rules(ModeRules),
+    Stats(ModeStats),
+    Calendar(ModeCalendar),
 }
 
 pub struct Globals {
     assets: Assets,
+    /// Whose daily challenges these are.
+    profile: String,
+    /// How hard new games should be, or `None` for any board.
+    tier: Option<Tier>,
 }
 
 pub enum Transition {
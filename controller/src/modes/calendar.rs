@@ -0,0 +1,179 @@
+use chrono::Datelike;
+use macroquad::prelude::*;
+use mofang_engine::daily::{self, days_in_month, DailyHistory, Date};
+use mofang_games::{MofangNode, SigmarNode};
+
+use crate::{
+    drawutils,
+    modes::{game::ModeGame, picker::ModePicker},
+    playable::Playable,
+    saves, Globals, Mode, Transition, HEX_HEIGHT, HEX_WIDTH,
+};
+
+/// Today's daily challenges, and how the past ones went.
+pub struct ModeCalendar {
+    today: Date,
+    /// The first day of the month being looked at.
+    month: Date,
+    history: DailyHistory,
+}
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Today, wherever the player is.
+pub fn today() -> Date {
+    let now = chrono::Local::now();
+    Date::new(now.year(), now.month(), now.day()).expect("chrono makes real dates")
+}
+
+impl ModeCalendar {
+    pub fn load() -> Self {
+        let today = today();
+        Self {
+            today,
+            month: Date::new(today.year, today.month, 1).unwrap(),
+            history: saves::load_daily_history(),
+        }
+    }
+
+    pub fn update(&mut self, globals: &mut Globals) -> Transition {
+        if is_key_pressed(KeyCode::Escape) {
+            return Transition::Swap(Mode::Picker(ModePicker));
+        }
+        if is_key_pressed(KeyCode::Left) {
+            // The last day of the month before, then the first day of that
+            let last = self.month.add_days(-1);
+            self.month = Date::new(last.year, last.month, 1).unwrap();
+        } else if is_key_pressed(KeyCode::Right) {
+            self.month = self
+                .month
+                .add_days(days_in_month(self.month.year, self.month.month) as i64);
+        }
+
+        let mouse_raw = mouse_position();
+        if !is_mouse_button_released(MouseButton::Left) {
+            Transition::None
+        } else if play_button(0).contains(mouse_raw.into()) {
+            self.play::<MofangNode>(globals)
+        } else if play_button(1).contains(mouse_raw.into()) {
+            self.play::<SigmarNode>(globals)
+        } else {
+            Transition::None
+        }
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        let grid_x = HEX_WIDTH * 0.5;
+        let grid_y = HEX_HEIGHT * 1.6;
+        let (cell_w, cell_h) = (HEX_WIDTH * 1.6, HEX_HEIGHT * 0.85);
+
+        drawutils::center_text(
+            globals,
+            "Daily Challenge",
+            32,
+            grid_x + cell_w * 3.5,
+            HEX_HEIGHT * 0.5,
+        );
+        drawutils::center_text(
+            globals,
+            &format!("< {}-{:02} >", self.month.year, self.month.month),
+            24,
+            grid_x + cell_w * 3.5,
+            HEX_HEIGHT * 1.05,
+        );
+        for (idx, name) in WEEKDAYS.iter().enumerate() {
+            drawutils::center_text(
+                globals,
+                name,
+                16,
+                grid_x + cell_w * (idx as f32 + 0.5),
+                grid_y - HEX_HEIGHT * 0.15,
+            );
+        }
+
+        let first_weekday = self.month.weekday() as usize;
+        for day in 1..=days_in_month(self.month.year, self.month.month) {
+            let date = Date::new(self.month.year, self.month.month, day).unwrap();
+            let slot = first_weekday + day as usize - 1;
+            let (x, y) = (
+                grid_x + cell_w * (slot % 7) as f32,
+                grid_y + cell_h * (slot / 7) as f32,
+            );
+            let color = if date == self.today { GOLD } else { LIGHTGRAY };
+            draw_rectangle_lines(x, y, cell_w, cell_h, 2.0, color);
+            drawutils::text(
+                globals,
+                &day.to_string(),
+                16,
+                x + 4.0,
+                y + 16.0,
+                drawutils::TextAlign::Left,
+            );
+            self.draw_result::<MofangNode>(globals, date, x, y + cell_h * 0.55);
+            self.draw_result::<SigmarNode>(globals, date, x, y + cell_h * 0.85);
+        }
+
+        self.draw_play::<MofangNode>(globals, 0);
+        self.draw_play::<SigmarNode>(globals, 1);
+        drawutils::center_text(
+            globals,
+            &format!(
+                "Playing as {}\nLeft and right to change months\nEscape to go back",
+                globals.profile
+            ),
+            16,
+            play_button(0).x + play_button(0).w / 2.0,
+            HEX_HEIGHT * 5.0,
+        );
+    }
+
+    /// How the profile did on that day's daily, in one line of a calendar cell.
+    fn draw_result<N: Playable>(&self, globals: &Globals, date: Date, x: f32, y: f32) {
+        if let Some(result) = self.history.get(&globals.profile, N::name(), date) {
+            let initial = N::display_name().chars().next().unwrap_or('?');
+            drawutils::text(
+                globals,
+                &format!("{} {}", initial, drawutils::clock(result.time)),
+                14,
+                x + 4.0,
+                y,
+                drawutils::TextAlign::Left,
+            );
+        }
+    }
+
+    fn draw_play<N: Playable>(&self, globals: &Globals, idx: usize) {
+        let rect = play_button(idx);
+        drawutils::button(globals, rect, N::display_name(), true);
+        let status = match self.history.get(&globals.profile, N::name(), self.today) {
+            Some(result) => format!(
+                "Cleared in {}, {} combos",
+                drawutils::clock(result.time),
+                result.moves
+            ),
+            None => "Not cleared yet".to_owned(),
+        };
+        drawutils::center_text(
+            globals,
+            &status,
+            16,
+            rect.x + rect.w / 2.0,
+            rect.y + rect.h + 14.0,
+        );
+    }
+
+    /// Play today's daily, picking up where we left off if it's the game in progress.
+    fn play<N: Playable>(&self, globals: &Globals) -> Transition {
+        let seed = daily::seed(N::name(), self.today);
+        Transition::Swap(ModeGame::<N>::resume_seed(&globals.profile, seed))
+    }
+}
+
+fn play_button(idx: usize) -> Rect {
+    Rect::new(
+        HEX_WIDTH * 12.0,
+        HEX_HEIGHT * (1.6 + idx as f32 * 1.3),
+        HEX_WIDTH * 3.5,
+        HEX_HEIGHT * 0.8,
+    )
+}
@@ -0,0 +1,114 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use macroquad::prelude::*;
+use mofang_engine::{
+    difficulty::{self, Tier},
+    Board,
+};
+
+use crate::{
+    drawutils, playable::Playable, Globals, Mode, Transition, HEX_HEIGHT, WINDOW_HEIGHT,
+    WINDOW_WIDTH,
+};
+
+use super::{game::ModeGame, picker::ModePicker};
+
+/// Making a board off on another thread, since the solver has to clear it first
+/// (and finding one in a tier solves and rates a lot of them).
+pub struct ModeFinding<N: Playable> {
+    profile: String,
+    looking: Looking,
+    /// Gets the seed and its board once they're made, or `None` if there wasn't one.
+    found: Receiver<Option<(u64, Board<N>)>>,
+}
+
+/// What `ModeFinding` is making a board for.
+#[derive(Clone, Copy)]
+enum Looking {
+    /// A new game on any board.
+    Any,
+    /// A new game on a board in this tier.
+    Tier(Tier),
+    /// The board this seed makes, since someone picked it.
+    Seed(u64),
+}
+
+impl<N: Playable> ModeFinding<N> {
+    /// Start making a new game for this profile, on a board from this tier if there is one.
+    pub fn new_game(profile: &str, tier: Option<Tier>) -> Self {
+        match tier {
+            Some(tier) => Self::start(profile, Looking::Tier(tier)),
+            None => Self::start(profile, Looking::Any),
+        }
+    }
+
+    /// Start making the board this seed makes for this profile.
+    pub fn seeded(profile: &str, seed: u64) -> Self {
+        Self::start(profile, Looking::Seed(seed))
+    }
+
+    fn start(profile: &str, looking: Looking) -> Self {
+        let (send, found) = mpsc::channel();
+        let seed = match looking {
+            Looking::Seed(seed) => seed,
+            _ => fastrand::u64(..),
+        };
+        thread::spawn(move || {
+            let seed = match looking {
+                Looking::Tier(tier) => difficulty::seed_in_tier::<N>(seed, tier),
+                _ => Some(seed),
+            };
+            let made = seed.and_then(|seed| Some((seed, N::new_solvable_game(seed)?)));
+            // Nobody's listening if the window was closed, which is fine
+            let _ = send.send(made);
+        });
+        Self {
+            profile: profile.to_owned(),
+            looking,
+            found,
+        }
+    }
+
+    pub fn update(&mut self, _globals: &mut Globals) -> Transition {
+        match self.found.try_recv() {
+            Ok(Some((seed, board))) => {
+                Transition::Swap(N::mode(ModeGame::on_board(&self.profile, seed, board)))
+            }
+            Err(TryRecvError::Empty) => Transition::None,
+            Ok(None) | Err(TryRecvError::Disconnected) => match self.looking {
+                Looking::Seed(seed) => {
+                    // Someone wanted this one, so don't swap in another
+                    println!("seed {} didn't make a board", seed);
+                    Transition::Swap(Mode::Picker(ModePicker))
+                }
+                Looking::Tier(tier) => {
+                    // Better to play something than nothing
+                    println!(
+                        "couldn't find a board that's {}, so playing any board",
+                        tier
+                    );
+                    Transition::Swap(N::finding(Self::new_game(&self.profile, None)))
+                }
+                Looking::Any => Transition::Swap(N::finding(Self::new_game(&self.profile, None))),
+            },
+        }
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        let text = match self.looking {
+            Looking::Any => "Making a board...".to_owned(),
+            Looking::Tier(tier) => format!("Finding a board that's {}...", tier),
+            Looking::Seed(seed) => format!("Making the board for seed {}...", seed),
+        };
+        drawutils::center_text(
+            globals,
+            &text,
+            32,
+            WINDOW_WIDTH / 2.0,
+            WINDOW_HEIGHT / 2.0 - HEX_HEIGHT * 0.25,
+        );
+    }
+}
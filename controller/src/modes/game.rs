@@ -1,95 +1,222 @@
+use std::time::Duration;
+
 use enum_map::EnumMap;
 use hex2d::{Coordinate, Spacing};
-use macroquad::prelude::*;
-use mofang_engine::{Board, Node, PartialResult};
-use mofang_games::MofangNode;
+use macroquad::{miniquad, prelude::*};
+use mofang_engine::{
+    daily,
+    difficulty::Tier,
+    replay::{Action, Replay},
+    stats::SeedBest,
+    Board, Click, Game, Solver, Status,
+};
 
-use crate::{drawutils, Globals, Mode, Transition, HEX_HEIGHT, HEX_SIZE, HEX_WIDTH, NODE_RADIUS};
+use crate::{
+    drawutils, playable::Playable, saves, Globals, Mode, Transition, HEX_HEIGHT, HEX_SIZE,
+    HEX_WIDTH, NODE_RADIUS,
+};
 
-use super::rules::ModeRules;
+use super::{
+    calendar, finding::ModeFinding, picker::ModePicker, playback::ModePlayback,
+    walkthrough::ModeWalkthrough,
+};
 
-pub struct ModeGame {
-    board: Board<MofangNode>,
+pub struct ModeGame<N: Playable> {
+    game: Game<N>,
+    /// Whose autosave this is, or `None` if it's just being watched and isn't saved.
+    profile: Option<String>,
     hovered_slot: Option<Coordinate>,
-    selected_slots: Vec<Coordinate>,
-    node_count: EnumMap<MofangNode, u32>,
-
-    won: bool,
+    node_count: EnumMap<N, u32>,
+    /// The combo we suggested, until the board changes.
+    hint: Option<Vec<Coordinate>>,
+    /// Something to tell the player, like that there's no walkthrough, until the board changes.
+    message: Option<&'static str>,
+    /// Whether this game's already been counted in the stats.
+    recorded: bool,
+    /// The board is hidden and the clock is stopped.
+    paused: bool,
+    /// The best anyone had done on this seed before it was won this time.
+    seed_best: Option<SeedBest>,
+    /// What's been typed into the seed box, while it's open.
+    seed_entry: Option<String>,
 }
 
-impl ModeGame {
-    pub fn new_game() -> Self {
+impl<N: Playable> ModeGame<N> {
+    /// Start a new game for this profile, on a board from this tier if there is one.
+    /// Making a board takes a while, so that goes off to `ModeFinding` first.
+    pub fn new_game(profile: &str, tier: Option<Tier>) -> Mode {
+        N::finding(ModeFinding::new_game(profile, tier))
+    }
+
+    /// Start a new game for this profile on the board this seed made.
+    /// Get the board from `ModeFinding`, since the solver has to clear it first.
+    pub fn on_board(profile: &str, seed: u64, board: Board<N>) -> Self {
+        let this = Self::with_game(Game::seeded(board, seed), Some(profile.to_owned()));
+        // Don't resume the old game next time
+        this.autosave();
+        this
+    }
+
+    /// Pick up this profile's autosaved game if it's on this seed, otherwise start the seed fresh.
+    pub fn resume_seed(profile: &str, seed: u64) -> Mode {
+        match saves::resume(profile) {
+            Some(game) if game.seed() == Some(seed) => {
+                N::mode(Self::with_game(game, Some(profile.to_owned())))
+            }
+            _ => N::finding(ModeFinding::seeded(profile, seed)),
+        }
+    }
+
+    /// Pick up this profile's autosaved game if there is one, otherwise start a new one.
+    pub fn resume_or_new_game(profile: &str, tier: Option<Tier>) -> Mode {
+        match saves::resume(profile) {
+            Some(game) => N::mode(Self::with_game(game, Some(profile.to_owned()))),
+            None => Self::new_game(profile, tier),
+        }
+    }
+
+    /// Show a game without playing it or saving it, like for a replay.
+    pub fn watching(game: Game<N>) -> Self {
+        Self::with_game(game, None)
+    }
+
+    fn with_game(game: Game<N>, profile: Option<String>) -> Self {
         let mut this = Self {
-            board: MofangNode::new_game_random(),
+            game,
+            profile,
             hovered_slot: None,
-            selected_slots: Vec::new(),
             node_count: EnumMap::new(),
-
-            won: false,
+            hint: None,
+            message: None,
+            recorded: false,
+            paused: false,
+            seed_best: None,
+            seed_entry: None,
         };
         this.update_node_count();
         this
     }
 
-    pub fn update(&mut self, _globals: &mut Globals) -> Transition {
-        if self.won {
+    pub fn update(&mut self, globals: &mut Globals) -> Transition {
+        if self.seed_entry.is_some() {
+            return self.update_seed_entry(&globals.profile);
+        }
+
+        let finished = self.game.status() != Status::Playing;
+        if finished {
             // Forbid interacting with the board
             self.hovered_slot = None;
-            self.selected_slots.clear();
         } else if is_key_pressed(KeyCode::H) {
-            return Transition::Push(Mode::Rules(ModeRules));
+            if let Some(rules) = N::rules() {
+                return Transition::Push(rules);
+            }
         }
 
         let mouse_raw = mouse_position();
-        if is_mouse_button_released(MouseButton::Left)
-            && new_game_button().contains(mouse_raw.into())
+        let clicked = is_mouse_button_released(MouseButton::Left);
+        let stuck = self.game.status() == Status::Lost;
+        let new_game = new_game_button().contains(mouse_raw.into())
+            || (stuck && self.stuck_new_game_button().contains(mouse_raw.into()));
+        if clicked && new_game {
+            self.give_up();
+            return Transition::Swap(ModeGame::<N>::new_game(&globals.profile, globals.tier));
+        }
+        if clicked && picker_button().contains(mouse_raw.into()) {
+            // It'll be resumed, so just save how long it took so far
+            self.autosave();
+            return Transition::Swap(Mode::Picker(ModePicker));
+        }
+
+        if is_key_pressed(KeyCode::C) || (clicked && copy_seed_button().contains(mouse_raw.into()))
         {
-            return Transition::Swap(Mode::Game(ModeGame::new_game()));
-        } else if self.won {
+            if let Some(seed) = self.game.seed() {
+                copy_to_clipboard(&seed.to_string());
+            }
+        }
+        if is_key_pressed(KeyCode::S) || (clicked && play_seed_button().contains(mouse_raw.into()))
+        {
+            self.seed_entry = Some(String::new());
+            self.hovered_slot = None;
+            return Transition::None;
+        }
+
+        let pause =
+            is_key_pressed(KeyCode::P) || (clicked && pause_button().contains(mouse_raw.into()));
+        if pause && !finished {
+            self.paused = !self.paused;
+            self.hovered_slot = None;
+        }
+        if self.paused {
+            // No peeking
+            return Transition::None;
+        }
+        self.game
+            .add_time(Duration::from_secs_f32(get_frame_time()));
+
+        let undo = is_key_pressed(KeyCode::Z)
+            || (clicked && undo_button().contains(mouse_raw.into()))
+            || (clicked && stuck && self.stuck_undo_button().contains(mouse_raw.into()));
+        let redo =
+            is_key_pressed(KeyCode::Y) || (clicked && redo_button().contains(mouse_raw.into()));
+        if (undo && self.game.undo()) || (redo && self.game.redo()) {
+            self.hint = None;
+            self.message = None;
+            self.update_node_count();
+            self.record_win(globals);
+            self.autosave();
+            return Transition::None;
+        } else if finished {
+            let watch_replay = is_key_pressed(KeyCode::R)
+                || (clicked && self.watch_replay_button().contains(mouse_raw.into()));
+            if watch_replay && self.game.status() == Status::Won {
+                if let Some(replay) = Replay::of(&self.game) {
+                    return watch::<N>(replay);
+                }
+            }
             return Transition::None;
         }
 
+        if is_key_pressed(KeyCode::Space) {
+            self.hint = self.game.hint(&Solver::default());
+            if self.hint.is_some() {
+                // So it's still counted as helped if it's resumed
+                self.autosave();
+            } else {
+                self.message = Some("No hint from here, try undoing");
+            }
+        }
+        if is_key_pressed(KeyCode::W) {
+            // Show how to clear it from here
+            match ModeWalkthrough::solve(self.game.board()) {
+                Some(walkthrough) => {
+                    self.game.mark_assisted();
+                    self.autosave();
+                    return Transition::Push(N::walkthrough(walkthrough));
+                }
+                None => self.message = Some("Couldn't find a way to clear it from here"),
+            }
+        }
+
         let dmouse_x = mouse_raw.0 - self.board_origin_x();
         let dmouse_y = mouse_raw.1 - self.board_origin_y();
 
         let hovered_coord =
             Coordinate::from_pixel(dmouse_x, dmouse_y, Spacing::PointyTop(HEX_SIZE));
-        self.hovered_slot = Some(hovered_coord).filter(|c| self.board.in_bounds(*c));
+        self.hovered_slot = Some(hovered_coord).filter(|c| self.game.board().in_bounds(*c));
 
         if let Some(hovered) = self.hovered_slot {
-            if is_mouse_button_released(MouseButton::Left) {
-                // hey we're trying to select something!
-                if let Some(idx) = self.selected_slots.iter().position(|c| c == &hovered) {
-                    // Oops we know this already
-                    if idx == self.selected_slots.len() - 1 {
-                        self.selected_slots.pop();
-                    } else {
-                        self.selected_slots.clear();
-                    }
-                } else if self.is_selectable(hovered) {
-                    self.selected_slots.push(hovered);
-                    // See if we have a WOMBO COMBO
-                    let combo: Vec<_> = self
-                        .selected_slots
-                        .iter()
-                        .flat_map(|&c| self.board.get_node(c))
-                        .collect();
-                    if let PartialResult::Success(change) = Node::select(&combo) {
+            if clicked {
+                match self.game.click(hovered) {
+                    Click::Ignored => {}
+                    Click::Selection => self.autosave(),
+                    Click::Matched => {
                         // nice!
-                        for (update, &slot) in change.into_iter().zip(self.selected_slots.iter()) {
-                            self.board.set_node(slot, update);
-                        }
-                        self.selected_slots.clear();
+                        self.hint = None;
+                        self.message = None;
                         self.update_node_count();
-
-                        if self.node_count.iter().all(|(_node, &count)| count == 0) {
-                            // poggers
-                            self.won = true;
-                        }
+                        self.record_win(globals);
+                        self.autosave();
                     }
-                } else if self.board.get_node(hovered).is_none() {
-                    // Click off a piece to clear your selection
-                    self.selected_slots.clear();
                 }
             }
         }
@@ -97,15 +224,145 @@ impl ModeGame {
         Transition::None
     }
 
+    pub fn game(&self) -> &Game<N> {
+        &self.game
+    }
+
+    /// Do something to the game from outside, like a replay does.
+    pub fn play_step(&mut self, action: Action) {
+        if action.apply(&mut self.game) {
+            self.hint = None;
+            self.update_node_count();
+        }
+    }
+
     pub fn draw(&self, globals: &Globals) {
-        // Draw counting UI
+        let hovered_node = self.draw_counter(globals);
+        let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+
+        // Draw buttons
+        drawutils::button(globals, new_game_button(), "New Game", true);
+        drawutils::button(globals, picker_button(), "Change Game", true);
+        drawutils::button(
+            globals,
+            undo_button(),
+            "Undo",
+            !self.game.history().is_empty(),
+        );
+        drawutils::button(
+            globals,
+            redo_button(),
+            "Redo",
+            !self.game.undone().is_empty(),
+        );
+
+        // Draw the HUD
+        let score = self.game.score();
+        drawutils::center_text(
+            globals,
+            &drawutils::clock(score.elapsed),
+            32,
+            ui_center_x - HEX_WIDTH * 0.4,
+            HEX_HEIGHT * 4.3,
+        );
+        let finished = self.game.status() != Status::Playing;
+        let pause_label = if self.paused { "Resume" } else { "Pause" };
+        drawutils::button(globals, pause_button(), pause_label, !finished);
+        drawutils::center_text(
+            globals,
+            &format!(
+                "{} combos, {} left",
+                score.moves,
+                self.game.board().node_count()
+            ),
+            18,
+            ui_center_x,
+            HEX_HEIGHT * 4.75,
+        );
+        drawutils::center_text(
+            globals,
+            &format!("Score: {}", score.points),
+            18,
+            ui_center_x,
+            HEX_HEIGHT * 5.15,
+        );
+        if let Some(message) = self.message {
+            drawutils::center_text(globals, message, 16, ui_center_x, HEX_HEIGHT * 5.55);
+        }
+
+        // Draw the seed, so it can be shared
+        let seed = match self.game.seed() {
+            Some(seed) if seed == daily::seed(N::name(), calendar::today()) => {
+                format!("Daily {} (seed {})", calendar::today(), seed)
+            }
+            Some(seed) => format!("Seed {}", seed),
+            None => "Unknown seed".to_owned(),
+        };
+        drawutils::text(
+            globals,
+            &seed,
+            16,
+            HEX_WIDTH * 0.2,
+            HEX_HEIGHT * 0.2,
+            drawutils::TextAlign::Left,
+        );
+        drawutils::button(
+            globals,
+            copy_seed_button(),
+            "Copy",
+            self.game.seed().is_some(),
+        );
+        drawutils::button(globals, play_seed_button(), "Play Seed", true);
+
+        if let Some(entry) = &self.seed_entry {
+            self.draw_seed_entry(globals, entry);
+            return;
+        }
+        if self.paused {
+            let (x, y) = (self.board_origin_x(), self.board_origin_y());
+            drawutils::center_text(globals, "Paused", 32, x, y - HEX_HEIGHT * 0.25);
+            drawutils::center_text(
+                globals,
+                "Press P to keep playing",
+                18,
+                x,
+                y + HEX_HEIGHT * 0.25,
+            );
+            return;
+        }
+
+        self.draw_board(globals, hovered_node);
+
+        match self.game.status() {
+            Status::Lost => self.draw_stuck(globals),
+            Status::Won => self.draw_won(globals),
+            Status::Playing => {}
+        }
+    }
+
+    /// Put the highlight over these slots.
+    pub fn draw_highlights(&self, globals: &Globals, coords: &[Coordinate]) {
+        for coord in coords {
+            let (x, y) = coord.to_pixel(Spacing::PointyTop(HEX_SIZE));
+            draw_texture(
+                globals.assets.textures.highlight,
+                x + self.board_origin_x() - NODE_RADIUS,
+                y + self.board_origin_y() - NODE_RADIUS,
+                WHITE,
+            );
+        }
+    }
+
+    /// How many of each node are left, in the corner.
+    /// Returns the node the mouse is over.
+    pub fn draw_counter(&self, globals: &Globals) -> Option<N> {
         let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
 
-        let pent_x = ui_center_x;
-        let pent_y = HEX_WIDTH * 2.5;
+        let counter_x = ui_center_x;
+        let counter_y = HEX_WIDTH * 2.5;
         let mouse_pos = mouse_position();
         let mut hovered_node = None;
-        drawutils::pentagram(globals, pent_x, pent_y, |x, y, angle, node| {
+        N::counter(globals, counter_x, counter_y, |x, y, angle, node| {
             let (dx, dy) = (mouse_pos.0 - x, mouse_pos.1 - y);
             if dx * dx + dy * dy < NODE_RADIUS * NODE_RADIUS {
                 hovered_node = Some(node.clone());
@@ -118,27 +375,13 @@ impl ModeGame {
             draw_circle_lines(x, y, NODE_RADIUS * 0.3, 1.2, BLACK);
             drawutils::center_text(globals, count.to_string().as_str(), 14, x, y);
         });
+        hovered_node
+    }
 
-        // Draw new game button
-        let new_game_button = new_game_button();
-        draw_rectangle_lines(
-            new_game_button.x,
-            new_game_button.y,
-            new_game_button.w,
-            new_game_button.h,
-            2.0,
-            BLACK,
-        );
-        drawutils::center_text(
-            globals,
-            "New Game",
-            20,
-            new_game_button.x + new_game_button.w / 2.0,
-            new_game_button.y + new_game_button.h / 2.0,
-        );
-
-        // Draw board
-        for hex_coord in Coordinate::new(0, 0).range_iter(self.board.radius()) {
+    /// Draw the board, fading out everything but the hovered node if there is one.
+    pub fn draw_board(&self, globals: &Globals, hovered_node: Option<N>) {
+        // (only the hexes the board has, so holes in the layout stay blank)
+        for (hex_coord, _) in self.game.board().nodes_iter() {
             let zero_coords = hex_coord.to_pixel(Spacing::PointyTop(HEX_SIZE));
             let coords = (
                 zero_coords.0 + self.board_origin_x(),
@@ -151,10 +394,10 @@ impl ModeGame {
                 WHITE,
             );
 
-            let unfaded_node = if let Some(node) = self.board.get_node(hex_coord) {
+            let unfaded_node = if let Some(node) = self.game.board().get_node(hex_coord) {
                 let unfaded = match hovered_node {
                     Some(ref hnode) => *hnode == *node,
-                    None => self.is_selectable(hex_coord),
+                    None => self.game.is_selectable(hex_coord),
                 };
                 drawutils::node(globals, node, coords.0, coords.1, !unfaded);
                 unfaded
@@ -165,14 +408,19 @@ impl ModeGame {
             let center_x = coords.0 - NODE_RADIUS;
             let center_y = coords.1 - NODE_RADIUS;
 
-            if self.selected_slots.contains(&hex_coord) {
+            if self.game.selected().contains(&hex_coord) {
                 draw_texture(globals.assets.textures.select, center_x, center_y, WHITE);
             } else if self.hovered_slot == Some(hex_coord) && unfaded_node {
                 draw_texture(globals.assets.textures.highlight, center_x, center_y, WHITE);
             }
 
+            let hinted = matches!(&self.hint, Some(hint) if hint.contains(&hex_coord));
+            if hinted {
+                draw_circle_lines(coords.0, coords.1, NODE_RADIUS + 2.0, 3.0, GOLD);
+            }
+
             if is_key_down(KeyCode::LeftShift) {
-                let open_count = self.board.max_open_neighbors(&hex_coord);
+                let open_count = self.game.board().max_open_neighbors(&hex_coord);
                 draw_text(
                     open_count.to_string().as_str(),
                     coords.0,
@@ -184,47 +432,281 @@ impl ModeGame {
         }
     }
 
-    /// TODO: This function should be part of MofangNode.
-    /// We shouldn't trust the controller to do stuff like this.
-    fn is_selectable(&self, coord: Coordinate) -> bool {
-        let node = match self.board.get_node(coord) {
+    /// The box for typing in a seed, over where the board goes.
+    fn draw_seed_entry(&self, globals: &Globals, entry: &str) {
+        let (x, y) = (self.board_origin_x(), self.board_origin_y());
+        drawutils::center_text(globals, "Play which seed?", 32, x, y - HEX_HEIGHT * 0.5);
+        let rect = Rect::new(
+            x - HEX_WIDTH * 2.5,
+            y - HEX_HEIGHT * 0.25,
+            HEX_WIDTH * 5.0,
+            HEX_HEIGHT * 0.5,
+        );
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, BLACK);
+        drawutils::center_text(globals, &format!("{}_", entry), 24, x, y);
+        drawutils::center_text(
+            globals,
+            "Enter to play, Escape to go back",
+            18,
+            x,
+            y + HEX_HEIGHT * 0.5,
+        );
+    }
+
+    /// How it went, and how that stacks up against the best on this seed.
+    fn draw_won(&self, globals: &Globals) {
+        let (x, y) = (self.board_origin_x(), self.board_origin_y());
+        let score = self.game.score();
+        drawutils::center_text(globals, "Cleared!", 32, x, y - HEX_HEIGHT * 0.5);
+        drawutils::center_text(
+            globals,
+            &format!(
+                "{} in {} combos, {} points",
+                drawutils::clock(score.elapsed),
+                score.moves,
+                score.points
+            ),
+            18,
+            x,
+            y,
+        );
+        let comparison = match (&self.seed_best, self.game.seed()) {
+            _ if self.game.assisted() => {
+                "Cleared with help, so it doesn't count for bests".to_owned()
+            }
+            (Some(best), _) => format!(
+                "Best on this seed: {} in {} combos, {} points",
+                drawutils::clock(best.time),
+                best.moves,
+                best.points
+            ),
+            (None, Some(_)) => "First win on this seed!".to_owned(),
+            (None, None) => String::new(),
+        };
+        drawutils::center_text(globals, &comparison, 18, x, y + HEX_HEIGHT * 0.35);
+        if self.game.seed().is_some() {
+            drawutils::button(globals, self.watch_replay_button(), "Watch Replay", true);
+        }
+    }
+
+    /// Gray out the board and offer a way out.
+    fn draw_stuck(&self, globals: &Globals) {
+        let (x, y) = (self.board_origin_x(), self.board_origin_y());
+        let radius = (self.game.board().radius() + 1) as f32;
+        draw_rectangle(
+            x - radius * HEX_WIDTH,
+            y - radius * HEX_HEIGHT * 0.75,
+            radius * HEX_WIDTH * 2.0,
+            radius * HEX_HEIGHT * 1.5,
+            Color::new(1.0, 1.0, 1.0, 0.7),
+        );
+        drawutils::center_text(globals, "No moves left!", 32, x, y - HEX_HEIGHT * 0.5);
+        drawutils::button(globals, self.stuck_undo_button(), "Undo", true);
+        drawutils::button(globals, self.stuck_new_game_button(), "New Game", true);
+    }
+
+    /// Type in a seed to play, while the seed box is open.
+    fn update_seed_entry(&mut self, profile: &str) -> Transition {
+        let entry = match &mut self.seed_entry {
             Some(it) => it,
-            None => return false,
+            None => return Transition::None,
         };
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        let mut typed: Vec<_> = std::iter::from_fn(get_char_pressed).collect();
+        if ctrl && is_key_pressed(KeyCode::V) {
+            typed.extend(paste_from_clipboard().chars());
+        }
+        // Seeds are just numbers, so ignore anything else (like the S that opened this)
+        for ch in typed.into_iter().filter(char::is_ascii_digit) {
+            if entry.len() < u64::MAX.to_string().len() {
+                entry.push(ch);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            entry.pop();
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.seed_entry = None;
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            // Too-big numbers just don't go anywhere
+            if let Ok(seed) = entry.parse() {
+                self.give_up();
+                return Transition::Swap(N::finding(ModeFinding::seeded(profile, seed)));
+            }
+        }
+        Transition::None
+    }
+
+    /// Save the game so this profile can pick it back up, unless it's only being watched.
+    fn autosave(&self) {
+        if let Some(profile) = &self.profile {
+            saves::autosave(profile, &self.game);
+        }
+    }
 
-        node.can_select(&self.board, &coord, self.selected_slots.as_slice()) && {
-            // check to see if this is an allowed combo
-            let potential_select: Vec<_> = self
-                .selected_slots
-                .iter()
-                .flat_map(|c| self.board.get_node(*c))
-                .chain(Some(node))
-                .collect();
-            Node::select(&potential_select).is_valid()
+    /// Giving up on a game counts as losing it.
+    fn give_up(&mut self) {
+        if !self.recorded && self.game.moves() > 0 {
+            saves::record_stats(&self.game);
+            saves::save_replay(&self.game);
+            self.recorded = true;
+        }
+    }
+
+    /// Count the game in the stats if it was just won,
+    /// and in the daily history if it's today's challenge.
+    fn record_win(&mut self, globals: &Globals) {
+        if !self.recorded && self.game.status() == Status::Won {
+            // Remember the old bests first so there's something to compare against
+            self.seed_best = self
+                .game
+                .seed()
+                .and_then(|seed| saves::load_stats().best_on_seed(N::name(), seed).cloned());
+            saves::record_stats(&self.game);
+            saves::save_replay(&self.game);
+            self.recorded = true;
+
+            let today = calendar::today();
+            if self.game.seed() == Some(daily::seed(N::name(), today)) {
+                saves::record_daily(&globals.profile, &self.game, today);
+            }
         }
     }
 
     fn update_node_count(&mut self) {
         self.node_count.clear();
-        for node in self.board.nodes_iter().flat_map(|(_, node)| node) {
+        for node in self.game.board().nodes_iter().flat_map(|(_, node)| node) {
             self.node_count[node.clone()] += 1;
         }
     }
 
+    fn stuck_undo_button(&self) -> Rect {
+        Rect::new(
+            self.board_origin_x() - HEX_WIDTH * 1.6,
+            self.board_origin_y() + HEX_HEIGHT * 0.25,
+            HEX_WIDTH * 1.5,
+            HEX_HEIGHT * 0.75,
+        )
+    }
+
+    fn stuck_new_game_button(&self) -> Rect {
+        Rect::new(
+            self.board_origin_x() + HEX_WIDTH * 0.1,
+            self.board_origin_y() + HEX_HEIGHT * 0.25,
+            HEX_WIDTH * 1.5,
+            HEX_HEIGHT * 0.75,
+        )
+    }
+
+    fn watch_replay_button(&self) -> Rect {
+        Rect::new(
+            self.board_origin_x() - HEX_WIDTH * 1.25,
+            self.board_origin_y() + HEX_HEIGHT * 0.7,
+            HEX_WIDTH * 2.5,
+            HEX_HEIGHT * 0.6,
+        )
+    }
+
     fn board_origin_x(&self) -> f32 {
-        (self.board.radius() + 1) as f32 * HEX_WIDTH
+        (self.game.board().radius() + 1) as f32 * HEX_WIDTH
     }
     fn board_origin_y(&self) -> f32 {
-        (self.board.radius() + 1) as f32 * HEX_HEIGHT * 0.75
+        (self.game.board().radius() + 1) as f32 * HEX_HEIGHT * 0.75
     }
 }
 
+/// Watch a finished game back from the start.
+fn watch<N: Playable>(replay: Replay) -> Transition {
+    match ModePlayback::<N>::new(replay) {
+        Ok(playback) => Transition::Push(N::playback(playback)),
+        Err(err) => {
+            println!("couldn't watch the replay: {}", err);
+            Transition::None
+        }
+    }
+}
+
+fn copy_seed_button() -> Rect {
+    Rect::new(
+        HEX_WIDTH * 0.2,
+        HEX_HEIGHT * 0.5,
+        HEX_WIDTH * 0.9,
+        HEX_HEIGHT * 0.45,
+    )
+}
+
+fn play_seed_button() -> Rect {
+    Rect::new(
+        HEX_WIDTH * 1.2,
+        HEX_HEIGHT * 0.5,
+        HEX_WIDTH * 1.6,
+        HEX_HEIGHT * 0.45,
+    )
+}
+
+// This macroquad doesn't have a safe way to get at the clipboard, so these go through miniquad.
+fn copy_to_clipboard(text: &str) {
+    // SAFETY: `get_internal_gl` is unsafe because macroquad's own drawing state could be
+    // left half-changed under it. We're on the main thread in between macroquad calls,
+    // only hand the context to the clipboard (which doesn't touch any GL state),
+    // and drop it before returning, so nothing else sees it.
+    let gl = unsafe { get_internal_gl() };
+    miniquad::clipboard::set(gl.quad_context, text);
+}
+
+fn paste_from_clipboard() -> String {
+    // SAFETY: the same as in `copy_to_clipboard`.
+    let gl = unsafe { get_internal_gl() };
+    miniquad::clipboard::get(gl.quad_context).unwrap_or_default()
+}
+
+fn pause_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x + HEX_WIDTH * 0.45,
+        HEX_HEIGHT * 4.05,
+        HEX_WIDTH * 1.05,
+        HEX_HEIGHT * 0.45,
+    )
+}
+
+fn undo_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x - HEX_WIDTH * 1.5,
+        HEX_HEIGHT * 5.6,
+        HEX_WIDTH * 1.4,
+        HEX_HEIGHT * 0.75,
+    )
+}
+
+fn redo_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x + HEX_WIDTH * 0.1,
+        HEX_HEIGHT * 5.6,
+        HEX_WIDTH * 1.4,
+        HEX_HEIGHT * 0.75,
+    )
+}
+
 fn new_game_button() -> Rect {
     let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
     Rect::new(
         ui_center_x - HEX_WIDTH * 1.5,
-        HEX_HEIGHT * 7.0,
+        HEX_HEIGHT * 6.6,
+        HEX_WIDTH * 3.0,
+        HEX_HEIGHT * 0.8,
+    )
+}
+
+fn picker_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x - HEX_WIDTH * 1.5,
+        HEX_HEIGHT * 7.6,
         HEX_WIDTH * 3.0,
-        HEX_HEIGHT,
+        HEX_HEIGHT * 0.8,
     )
 }
@@ -1,2 +1,8 @@
+pub mod calendar;
+pub mod finding;
 pub mod game;
+pub mod picker;
+pub mod playback;
 pub mod rules;
+pub mod stats;
+pub mod walkthrough;
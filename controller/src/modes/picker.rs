@@ -0,0 +1,99 @@
+use macroquad::prelude::*;
+use mofang_engine::difficulty::Tier;
+use mofang_games::{MofangNode, SigmarNode};
+
+use crate::{
+    drawutils,
+    modes::{calendar::ModeCalendar, game::ModeGame, stats::ModeStats},
+    playable::Playable,
+    saves, Globals, Mode, Transition, HEX_HEIGHT, HEX_WIDTH, WINDOW_WIDTH,
+};
+
+/// Pick which game to play.
+pub struct ModePicker;
+
+impl ModePicker {
+    pub fn update(&mut self, globals: &mut Globals) -> Transition {
+        let mouse_raw = mouse_position();
+        if !is_mouse_button_released(MouseButton::Left) {
+            return Transition::None;
+        }
+
+        if game_button(0).contains(mouse_raw.into()) {
+            pick::<MofangNode>(globals)
+        } else if game_button(1).contains(mouse_raw.into()) {
+            pick::<SigmarNode>(globals)
+        } else if game_button(2).contains(mouse_raw.into()) {
+            Transition::Push(Mode::Stats(ModeStats::load()))
+        } else if game_button(3).contains(mouse_raw.into()) {
+            Transition::Swap(Mode::Calendar(ModeCalendar::load()))
+        } else if game_button(4).contains(mouse_raw.into()) {
+            globals.tier = next_tier(globals.tier);
+            Transition::None
+        } else {
+            Transition::None
+        }
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        drawutils::center_text(
+            globals,
+            "Pick a Game",
+            32,
+            WINDOW_WIDTH / 2.0,
+            HEX_HEIGHT * 1.5,
+        );
+        self.draw_game::<MofangNode>(globals, 0);
+        self.draw_game::<SigmarNode>(globals, 1);
+        drawutils::button(globals, game_button(2), "Statistics", true);
+        drawutils::button(globals, game_button(3), "Daily Challenge", true);
+        let tier = match globals.tier {
+            Some(Tier::Easy) => "New Games: Easy",
+            Some(Tier::Normal) => "New Games: Normal",
+            Some(Tier::Hard) => "New Games: Hard",
+            None => "New Games: Any",
+        };
+        drawutils::button(globals, game_button(4), tier, true);
+    }
+
+    fn draw_game<N: Playable>(&self, globals: &Globals, idx: usize) {
+        let rect = game_button(idx);
+        drawutils::button(globals, rect, N::display_name(), true);
+        if saves::has_autosave::<N>(&globals.profile) {
+            drawutils::center_text(
+                globals,
+                "(in progress)",
+                16,
+                rect.x + rect.w / 2.0,
+                rect.y + rect.h + 16.0,
+            );
+        }
+    }
+}
+
+/// Pick up where we left off in that game.
+fn pick<N: Playable>(globals: &Globals) -> Transition {
+    Transition::Swap(ModeGame::<N>::resume_or_new_game(
+        &globals.profile,
+        globals.tier,
+    ))
+}
+
+/// Any, then easiest to hardest, then back around.
+fn next_tier(tier: Option<Tier>) -> Option<Tier> {
+    match tier {
+        None => Some(Tier::Easy),
+        Some(Tier::Easy) => Some(Tier::Normal),
+        Some(Tier::Normal) => Some(Tier::Hard),
+        Some(Tier::Hard) => None,
+    }
+}
+
+fn game_button(idx: usize) -> Rect {
+    Rect::new(
+        WINDOW_WIDTH / 2.0 - HEX_WIDTH * 2.0,
+        HEX_HEIGHT * (2.4 + idx as f32 * 1.3),
+        HEX_WIDTH * 4.0,
+        HEX_HEIGHT,
+    )
+}
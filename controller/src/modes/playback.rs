@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use macroquad::prelude::*;
+use mofang_engine::{replay::Replay, save::LoadError, Board, Game};
+
+use crate::{drawutils, playable::Playable, Globals, Transition, HEX_HEIGHT, HEX_WIDTH};
+
+use super::game::ModeGame;
+
+/// Watching a recorded game play out, with the same board as playing it.
+pub struct ModePlayback<N: Playable> {
+    replay: Replay,
+    /// The board the replay starts on, so seeking back doesn't have to make it again.
+    start: Board<N>,
+    view: ModeGame<N>,
+    /// How many of the replay's steps have been played.
+    played: usize,
+    /// How far into the replay we are.
+    time: Duration,
+    playing: bool,
+    /// How many times faster than it was played.
+    speed: u32,
+}
+
+const MAX_SPEED: u32 = 16;
+
+impl<N: Playable> ModePlayback<N> {
+    /// Get ready to watch the replay from the start.
+    /// This makes the board from the seed, so it can take a moment.
+    pub fn new(replay: Replay) -> Result<Self, LoadError> {
+        let start = replay.board::<N>()?;
+        let view = ModeGame::watching(Game::seeded(start.clone(), replay.seed));
+        Ok(Self {
+            replay,
+            start,
+            view,
+            played: 0,
+            time: Duration::default(),
+            playing: true,
+            speed: 1,
+        })
+    }
+
+    pub fn update(&mut self, _globals: &mut Globals) -> Transition {
+        if is_key_pressed(KeyCode::Escape) {
+            return Transition::Pop;
+        }
+
+        let mouse_raw = mouse_position();
+        let clicked = is_mouse_button_released(MouseButton::Left);
+        let at_end = self.played == self.replay.steps.len();
+        if is_key_pressed(KeyCode::Space) || (clicked && play_button().contains(mouse_raw.into())) {
+            if at_end && !self.playing {
+                self.seek(0);
+            }
+            self.playing = !self.playing;
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.playing = false;
+            self.seek(self.played.saturating_sub(1));
+        } else if is_key_pressed(KeyCode::Right) && !at_end {
+            self.playing = false;
+            self.seek(self.played + 1);
+        } else if is_key_pressed(KeyCode::Home) {
+            self.seek(0);
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.speed = (self.speed * 2).min(MAX_SPEED);
+        } else if is_key_pressed(KeyCode::Down) {
+            self.speed = (self.speed / 2).max(1);
+        }
+
+        let bar = seek_bar();
+        if clicked && bar.contains(mouse_raw.into()) {
+            let fraction = ((mouse_raw.0 - bar.x) / bar.w).clamp(0.0, 1.0);
+            self.seek_time(self.replay.length().mul_f32(fraction));
+        }
+
+        if self.playing {
+            let time = self.time + Duration::from_secs_f32(get_frame_time()) * self.speed;
+            self.seek_time(time);
+            if self.played == self.replay.steps.len() {
+                self.playing = false;
+                self.time = self.replay.length();
+            }
+        }
+
+        Transition::None
+    }
+
+    /// Jump to just after this many steps were played.
+    fn seek(&mut self, played: usize) {
+        if played < self.played {
+            // Games can't be rewound, so play it again from the start
+            self.view = ModeGame::watching(Game::seeded(self.start.clone(), self.replay.seed));
+            self.played = 0;
+        }
+        while self.played < played {
+            self.view.play_step(self.replay.steps[self.played].action);
+            self.played += 1;
+        }
+        self.time = match played {
+            0 => Duration::default(),
+            played => self.replay.steps[played - 1].at,
+        };
+    }
+
+    /// Jump to this far into the replay.
+    fn seek_time(&mut self, time: Duration) {
+        let played = self
+            .replay
+            .steps
+            .iter()
+            .take_while(|step| step.at <= time)
+            .count();
+        self.seek(played);
+        self.time = time;
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        let hovered_node = self.view.draw_counter(globals);
+        self.view.draw_board(globals, hovered_node);
+
+        let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+        drawutils::text(
+            globals,
+            &format!("Replay of seed {}", self.replay.seed),
+            16,
+            HEX_WIDTH * 0.2,
+            HEX_HEIGHT * 0.2,
+            drawutils::TextAlign::Left,
+        );
+        drawutils::center_text(
+            globals,
+            &format!(
+                "{} / {}",
+                drawutils::clock(self.time),
+                drawutils::clock(self.replay.length())
+            ),
+            24,
+            ui_center_x,
+            HEX_HEIGHT * 4.3,
+        );
+        drawutils::center_text(
+            globals,
+            &format!(
+                "Step {} of {}, {} left",
+                self.played,
+                self.replay.steps.len(),
+                self.view.game().board().node_count()
+            ),
+            18,
+            ui_center_x,
+            HEX_HEIGHT * 4.75,
+        );
+        drawutils::center_text(
+            globals,
+            &format!("{}x speed", self.speed),
+            18,
+            ui_center_x,
+            HEX_HEIGHT * 5.15,
+        );
+
+        let label = if self.playing { "Pause" } else { "Play" };
+        drawutils::button(globals, play_button(), label, true);
+
+        let bar = seek_bar();
+        draw_rectangle_lines(bar.x, bar.y, bar.w, bar.h, 2.0, BLACK);
+        let length = self.replay.length().as_secs_f32();
+        let progress = if length > 0.0 {
+            (self.time.as_secs_f32() / length).min(1.0)
+        } else {
+            1.0
+        };
+        draw_rectangle(bar.x, bar.y, bar.w * progress, bar.h, GRAY);
+
+        drawutils::center_text(
+            globals,
+            "Space to play or pause\nLeft and right to step\nUp and down to change speed\nEscape to go back",
+            16,
+            ui_center_x,
+            HEX_HEIGHT * 7.4,
+        );
+    }
+}
+
+fn play_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x - HEX_WIDTH * 1.5,
+        HEX_HEIGHT * 5.6,
+        HEX_WIDTH * 3.0,
+        HEX_HEIGHT * 0.75,
+    )
+}
+
+fn seek_bar() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x - HEX_WIDTH * 1.5,
+        HEX_HEIGHT * 6.6,
+        HEX_WIDTH * 3.0,
+        HEX_HEIGHT * 0.3,
+    )
+}
@@ -0,0 +1,96 @@
+use crate::{drawutils, saves, Globals, Transition, HEX_HEIGHT, HEX_WIDTH, WINDOW_WIDTH};
+
+use macroquad::prelude::*;
+use mofang_engine::stats::Stats;
+
+/// How it's been going, for every game and board size.
+pub struct ModeStats {
+    stats: Stats,
+}
+
+const COLUMNS: [&str; 8] = [
+    "Game",
+    "Size",
+    "Played",
+    "Won",
+    "Streak",
+    "Best Streak",
+    "Best Time",
+    "Best Score",
+];
+
+impl ModeStats {
+    pub fn load() -> Self {
+        Self {
+            stats: saves::load_stats(),
+        }
+    }
+
+    pub fn update(&mut self, _globals: &mut Globals) -> Transition {
+        if is_key_pressed(KeyCode::Escape) {
+            Transition::Pop
+        } else {
+            Transition::None
+        }
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        drawutils::center_text(
+            globals,
+            "Statistics",
+            32,
+            WINDOW_WIDTH / 2.0,
+            HEX_HEIGHT / 2.0,
+        );
+
+        let text_size = (HEX_HEIGHT / 4.0) as u16;
+        let column_width = (WINDOW_WIDTH - HEX_WIDTH) / COLUMNS.len() as f32;
+        let row = |idx: usize, cells: &[String]| {
+            let y = HEX_HEIGHT * (1.2 + idx as f32 * 0.4);
+            for (col, cell) in cells.iter().enumerate() {
+                let x = HEX_WIDTH / 2.0 + column_width * (col as f32 + 0.5);
+                drawutils::center_text(globals, cell, text_size, x, y);
+            }
+        };
+
+        let header: Vec<_> = COLUMNS.iter().map(|c| c.to_string()).collect();
+        row(0, &header);
+        if self.stats.records().is_empty() {
+            drawutils::center_text(
+                globals,
+                "No games finished yet!",
+                text_size,
+                WINDOW_WIDTH / 2.0,
+                HEX_HEIGHT * 2.0,
+            );
+        }
+        for (idx, record) in self.stats.records().iter().enumerate() {
+            let or_dash = |it: Option<String>| it.unwrap_or_else(|| "-".to_owned());
+            row(
+                idx + 1,
+                &[
+                    record.game.clone(),
+                    if record.shape == "hexagon" {
+                        record.diameter.to_string()
+                    } else {
+                        format!("{} {}", record.diameter, record.shape)
+                    },
+                    record.played.to_string(),
+                    record.won.to_string(),
+                    record.streak.to_string(),
+                    record.best_streak.to_string(),
+                    or_dash(record.best_time.map(drawutils::clock)),
+                    or_dash(record.best_score.map(|s| s.to_string())),
+                ],
+            );
+        }
+
+        drawutils::center_text(
+            globals,
+            "Press Escape to go back",
+            text_size,
+            WINDOW_WIDTH / 2.0,
+            HEX_HEIGHT * 8.2,
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use macroquad::prelude::*;
+use mofang_engine::{replay::Action, solution, Board, Game, Move, Solution, Solver};
+
+use crate::{drawutils, playable::Playable, Globals, Transition, HEX_HEIGHT, HEX_WIDTH};
+
+use super::game::ModeGame;
+
+/// Showing a solution one combo at a time, clicking each node in turn.
+pub struct ModeWalkthrough<N: Playable> {
+    /// The board the solution starts on, so stepping back can play it again.
+    start: Board<N>,
+    steps: Vec<Move<N>>,
+    view: ModeGame<N>,
+    /// How many combos have been made.
+    made: usize,
+    /// How many nodes of the next combo have been clicked.
+    clicked: usize,
+    /// Seconds until the next click.
+    wait: f32,
+    playing: bool,
+}
+
+/// Seconds between clicks.
+const CLICK_TIME: f32 = 0.4;
+/// Seconds to show the next combo before clicking it.
+const LOOK_TIME: f32 = 0.8;
+
+impl<N: Playable> ModeWalkthrough<N> {
+    /// Find a solution from this board, if the solver can.
+    pub fn solve(board: &Board<N>) -> Option<Self> {
+        let line = match Solver::default().solve(board) {
+            Solution::Solved(line) => line,
+            _ => return None,
+        };
+        let steps = solution::steps(board, &line).ok()?;
+        Some(Self {
+            start: board.clone(),
+            steps,
+            view: ModeGame::watching(Game::new(board.clone())),
+            made: 0,
+            clicked: 0,
+            wait: LOOK_TIME,
+            playing: true,
+        })
+    }
+
+    pub fn update(&mut self, _globals: &mut Globals) -> Transition {
+        if is_key_pressed(KeyCode::Escape) {
+            return Transition::Pop;
+        }
+
+        let mouse_raw = mouse_position();
+        let clicked = is_mouse_button_released(MouseButton::Left);
+        if is_key_pressed(KeyCode::Space) || (clicked && play_button().contains(mouse_raw.into())) {
+            if self.made == self.steps.len() && !self.playing {
+                self.rewind(0);
+            }
+            self.playing = !self.playing;
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.playing = false;
+            // Back to the start of this combo, or the one before if it hasn't started
+            let to = if self.clicked > 0 {
+                self.made
+            } else {
+                self.made.saturating_sub(1)
+            };
+            self.rewind(to);
+        } else if is_key_pressed(KeyCode::Right) {
+            self.playing = false;
+            self.finish_combo();
+        }
+
+        if self.playing && self.made < self.steps.len() {
+            self.wait -= get_frame_time();
+            if self.wait <= 0.0 {
+                self.click_next();
+            }
+        } else if self.made == self.steps.len() {
+            self.playing = false;
+        }
+
+        Transition::None
+    }
+
+    /// Click the next node of the next combo.
+    fn click_next(&mut self) {
+        let coord = self.steps[self.made].coords[self.clicked];
+        self.view.play_step(Action::Click(coord));
+        self.clicked += 1;
+        if self.clicked == self.steps[self.made].coords.len() {
+            self.made += 1;
+            self.clicked = 0;
+            self.wait = LOOK_TIME;
+        } else {
+            self.wait = CLICK_TIME;
+        }
+    }
+
+    fn finish_combo(&mut self) {
+        if self.made < self.steps.len() {
+            let made = self.made;
+            while self.made == made {
+                self.click_next();
+            }
+        }
+    }
+
+    /// Go back to just before this combo.
+    fn rewind(&mut self, combo: usize) {
+        self.view = ModeGame::watching(Game::new(self.start.clone()));
+        self.made = 0;
+        self.clicked = 0;
+        while self.made < combo {
+            self.finish_combo();
+        }
+        self.wait = LOOK_TIME;
+    }
+
+    pub fn draw(&self, globals: &Globals) {
+        let hovered_node = self.view.draw_counter(globals);
+        self.view.draw_board(globals, hovered_node);
+        let next = self.steps.get(self.made);
+        if let Some(next) = next {
+            // What's about to be clicked
+            self.view
+                .draw_highlights(globals, &next.coords[self.clicked..]);
+        }
+
+        let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+        drawutils::text(
+            globals,
+            &match next {
+                Some(next) => format!(
+                    "Combo {} of {}: {}",
+                    self.made + 1,
+                    self.steps.len(),
+                    solution::describe(next)
+                ),
+                None => format!("Cleared in {} combos", self.steps.len()),
+            },
+            16,
+            HEX_WIDTH * 0.2,
+            HEX_HEIGHT * 0.2,
+            drawutils::TextAlign::Left,
+        );
+        drawutils::center_text(
+            globals,
+            &format!(
+                "{} combos to go, {} left",
+                self.steps.len() - self.made,
+                self.view.game().board().node_count()
+            ),
+            18,
+            ui_center_x,
+            HEX_HEIGHT * 4.75,
+        );
+
+        let label = if self.playing { "Pause" } else { "Play" };
+        drawutils::button(globals, play_button(), label, true);
+        drawutils::center_text(
+            globals,
+            "Space to play or pause\nLeft and right to step\nEscape to go back",
+            16,
+            ui_center_x,
+            HEX_HEIGHT * 7.0,
+        );
+    }
+}
+
+fn play_button() -> Rect {
+    let ui_center_x = screen_width() - HEX_WIDTH * 2.3;
+    Rect::new(
+        ui_center_x - HEX_WIDTH * 1.5,
+        HEX_HEIGHT * 5.6,
+        HEX_WIDTH * 3.0,
+        HEX_HEIGHT * 0.75,
+    )
+}
@@ -0,0 +1,151 @@
+use std::hash::Hash;
+
+use enum_map::Enum;
+use macroquad::prelude::*;
+use mofang_engine::Node;
+use mofang_games::{MofangNode, SigmarNode};
+
+use crate::{
+    assets::Textures,
+    drawutils,
+    modes::{
+        finding::ModeFinding, game::ModeGame, playback::ModePlayback, rules::ModeRules,
+        walkthrough::ModeWalkthrough,
+    },
+    Globals, Mode,
+};
+
+/// Everything the controller needs to know to put a game on screen.
+/// Boards get made on another thread, so they have to be `Send`.
+pub trait Playable: Node + Clone + Hash + Eq + Enum<u32> + Send + Sized + 'static {
+    /// The name to show in the game picker.
+    fn display_name() -> &'static str;
+
+    fn texture(&self, textures: &Textures) -> Texture2D;
+
+    /// Draw the counting UI centered on (x, y).
+    ///
+    /// The continuation gets called with the center of each node drawn,
+    /// the angle to put its counter at, and the node.
+    fn counter<C>(globals: &Globals, x: f32, y: f32, continuation: C)
+    where
+        C: FnMut(f32, f32, f32, Self);
+
+    /// Wrap a game of this up so it can go on the mode stack.
+    fn mode(game: ModeGame<Self>) -> Mode;
+
+    /// Wrap a replay of this up so it can go on the mode stack.
+    fn playback(playback: ModePlayback<Self>) -> Mode;
+
+    /// Wrap a walkthrough of this up so it can go on the mode stack.
+    fn walkthrough(walkthrough: ModeWalkthrough<Self>) -> Mode;
+
+    /// Wrap a search for a board of this up so it can go on the mode stack.
+    fn finding(finding: ModeFinding<Self>) -> Mode;
+
+    /// The rules screen, if there is one.
+    fn rules() -> Option<Mode> {
+        None
+    }
+}
+
+impl Playable for MofangNode {
+    fn display_name() -> &'static str {
+        "Mofang's Garden"
+    }
+
+    fn texture(&self, textures: &Textures) -> Texture2D {
+        match self {
+            MofangNode::Wood => textures.wood,
+            MofangNode::Fire => textures.fire,
+            MofangNode::Earth => textures.earth,
+            MofangNode::Metal => textures.metal,
+            MofangNode::Water => textures.water,
+            MofangNode::Heavenly => textures.heavenly,
+            MofangNode::Earthly => textures.earthly,
+            MofangNode::Human => textures.human,
+            MofangNode::Yin => textures.yin,
+            MofangNode::Yang => textures.yang,
+            MofangNode::Creation => textures.creation,
+            MofangNode::Destruction => textures.destruction,
+            MofangNode::Qi => textures.qi,
+        }
+    }
+
+    fn counter<C>(globals: &Globals, x: f32, y: f32, continuation: C)
+    where
+        C: FnMut(f32, f32, f32, Self),
+    {
+        drawutils::pentagram(globals, x, y, continuation);
+    }
+
+    fn mode(game: ModeGame<Self>) -> Mode {
+        Mode::MofangGame(game)
+    }
+
+    fn playback(playback: ModePlayback<Self>) -> Mode {
+        Mode::MofangPlayback(playback)
+    }
+
+    fn walkthrough(walkthrough: ModeWalkthrough<Self>) -> Mode {
+        Mode::MofangWalkthrough(walkthrough)
+    }
+
+    fn finding(finding: ModeFinding<Self>) -> Mode {
+        Mode::MofangFinding(finding)
+    }
+
+    fn rules() -> Option<Mode> {
+        Some(Mode::Rules(ModeRules))
+    }
+}
+
+impl Playable for SigmarNode {
+    fn display_name() -> &'static str {
+        "Sigmar's Garden"
+    }
+
+    fn texture(&self, textures: &Textures) -> Texture2D {
+        let textures = &textures.sigmar;
+        match self {
+            SigmarNode::Salt => textures.salt,
+            SigmarNode::Quintessence => textures.quintessence,
+            SigmarNode::Water => textures.water,
+            SigmarNode::Earth => textures.earth,
+            SigmarNode::Fire => textures.fire,
+            SigmarNode::Air => textures.air,
+            SigmarNode::Quicksilver => textures.quicksilver,
+            SigmarNode::Lead => textures.lead,
+            SigmarNode::Tin => textures.tin,
+            SigmarNode::Iron => textures.iron,
+            SigmarNode::Copper => textures.copper,
+            SigmarNode::Silver => textures.silver,
+            SigmarNode::Gold => textures.gold,
+            SigmarNode::Vitae => textures.vitae,
+            SigmarNode::Mors => textures.mors,
+        }
+    }
+
+    fn counter<C>(globals: &Globals, x: f32, y: f32, continuation: C)
+    where
+        C: FnMut(f32, f32, f32, Self),
+    {
+        drawutils::sigmar_table(globals, x, y, continuation);
+    }
+
+    fn mode(game: ModeGame<Self>) -> Mode {
+        Mode::SigmarGame(game)
+    }
+
+    fn playback(playback: ModePlayback<Self>) -> Mode {
+        Mode::SigmarPlayback(playback)
+    }
+
+    fn walkthrough(walkthrough: ModeWalkthrough<Self>) -> Mode {
+        Mode::SigmarWalkthrough(walkthrough)
+    }
+
+    fn finding(finding: ModeFinding<Self>) -> Mode {
+        Mode::SigmarFinding(finding)
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use mofang_engine::{
+    daily::{DailyHistory, Date},
+    replay::Replay,
+    save,
+    stats::Stats,
+    Game, Node, Status,
+};
+use once_cell::sync::Lazy;
+
+/// Path to the folder saves go in
+static SAVES_ROOT: Lazy<PathBuf> = Lazy::new(|| {
+    if cfg!(debug_assertions) && !cfg!(target_arch = "wasm32") {
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../saves"))
+    } else {
+        PathBuf::from("saves")
+    }
+});
+
+/// Each profile has its own game in progress, so nobody picks up (or clobbers) someone else's.
+fn autosave_path<N: Node>(profile: &str) -> PathBuf {
+    // Keep slashes and such in the name from going anywhere funny
+    let profile: String = profile
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    SAVES_ROOT.join(format!("autosave_{}_{}.txt", N::name(), profile))
+}
+
+/// Save the game so this profile can pick it back up next time.
+/// Won games don't need resuming, so their autosave is deleted instead.
+/// Lost games are kept around so the last move can still be undone.
+pub fn autosave<N: Node + Clone>(profile: &str, game: &Game<N>) {
+    let path = autosave_path::<N>(profile);
+    let result = if game.status() != Status::Won {
+        fs::create_dir_all(SAVES_ROOT.as_path()).and_then(|_| save::save_to_file(game, &path))
+    } else if path.exists() {
+        fs::remove_file(&path)
+    } else {
+        Ok(())
+    };
+    if let Err(err) = result {
+        println!("couldn't autosave to {}: {}", path.display(), err);
+    }
+}
+
+/// Does this profile have a game of this in progress?
+pub fn has_autosave<N: Node>(profile: &str) -> bool {
+    autosave_path::<N>(profile).exists()
+}
+
+/// Load this profile's last autosaved game, if there is one.
+pub fn resume<N: Node + Clone>(profile: &str) -> Option<Game<N>> {
+    let path = autosave_path::<N>(profile);
+    if !path.exists() {
+        return None;
+    }
+    match save::load_from_file(&path) {
+        Ok(game) => Some(game),
+        Err(err) => {
+            println!("couldn't resume from {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn stats_path() -> PathBuf {
+    SAVES_ROOT.join("stats.txt")
+}
+
+/// Read everyone's stats, with fresh ones if there aren't any yet.
+fn read_stats(path: &Path) -> Result<Stats, save::LoadError> {
+    if !path.exists() {
+        return Ok(Stats::new());
+    }
+    fs::read_to_string(path)
+        .map_err(save::LoadError::from)
+        .and_then(|text| Stats::read(&text))
+}
+
+/// Load everyone's stats, or start fresh if there aren't any yet.
+pub fn load_stats() -> Stats {
+    let path = stats_path();
+    read_stats(&path).unwrap_or_else(|err| {
+        println!("couldn't load stats from {}: {}", path.display(), err);
+        Stats::new()
+    })
+}
+
+/// Count a won or abandoned game in the stats.
+///
+/// If the stats that are there can't be read, this leaves them alone
+/// instead of writing over them with just this game.
+pub fn record_stats<N: Node + Clone>(game: &Game<N>) {
+    let path = stats_path();
+    let mut stats = match read_stats(&path) {
+        Ok(stats) => stats,
+        Err(err) => {
+            println!(
+                "not counting this game, since the stats in {} couldn't be read: {}",
+                path.display(),
+                err
+            );
+            return;
+        }
+    };
+    stats.record(game);
+    let result =
+        fs::create_dir_all(SAVES_ROOT.as_path()).and_then(|_| fs::write(&path, stats.write()));
+    if let Err(err) = result {
+        println!("couldn't save stats to {}: {}", path.display(), err);
+    }
+}
+
+fn daily_path() -> PathBuf {
+    SAVES_ROOT.join("daily.txt")
+}
+
+/// Read everyone's daily challenge results, with a fresh history if there isn't one yet.
+fn read_daily_history(path: &Path) -> Result<DailyHistory, save::LoadError> {
+    if !path.exists() {
+        return Ok(DailyHistory::new());
+    }
+    fs::read_to_string(path)
+        .map_err(save::LoadError::from)
+        .and_then(|text| DailyHistory::read(&text))
+}
+
+/// Load everyone's daily challenge results, or start fresh if there aren't any yet.
+pub fn load_daily_history() -> DailyHistory {
+    let path = daily_path();
+    read_daily_history(&path).unwrap_or_else(|err| {
+        println!(
+            "couldn't load daily history from {}: {}",
+            path.display(),
+            err
+        );
+        DailyHistory::new()
+    })
+}
+
+/// Write down a cleared daily challenge, if it's the first time this profile cleared it.
+///
+/// If the history that's there can't be read, this leaves it alone
+/// instead of writing over everyone's results with just this one.
+pub fn record_daily<N: Node + Clone>(profile: &str, game: &Game<N>, date: Date) {
+    let path = daily_path();
+    let mut history = match read_daily_history(&path) {
+        Ok(history) => history,
+        Err(err) => {
+            println!(
+                "not recording this daily, since the history in {} couldn't be read: {}",
+                path.display(),
+                err
+            );
+            return;
+        }
+    };
+    if !history.record(profile, game, date) {
+        return;
+    }
+    let result =
+        fs::create_dir_all(SAVES_ROOT.as_path()).and_then(|_| fs::write(&path, history.write()));
+    if let Err(err) = result {
+        println!("couldn't save daily history to {}: {}", path.display(), err);
+    }
+}
+
+fn replays_path() -> PathBuf {
+    SAVES_ROOT.join("replays")
+}
+
+/// Keep a recording of a finished game, named after the game, seed and when it finished.
+pub fn save_replay<N: Node + Clone>(game: &Game<N>) {
+    let replay = match Replay::of(game) {
+        Some(replay) if !replay.steps.is_empty() => replay,
+        _ => return,
+    };
+    let finished = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let path = replays_path().join(format!("{}_{}_{}.txt", N::name(), replay.seed, finished));
+    let result = fs::create_dir_all(replays_path()).and_then(|_| fs::write(&path, replay.write()));
+    if let Err(err) = result {
+        println!("couldn't save replay to {}: {}", path.display(), err);
+    }
+}
+
+/// Load a replay from anywhere, like one a teammate sent over.
+pub fn load_replay(path: impl AsRef<Path>) -> Option<Replay> {
+    let path = path.as_ref();
+    match fs::read_to_string(path)
+        .map_err(save::LoadError::from)
+        .and_then(|text| Replay::read(&text))
+    {
+        Ok(replay) => Some(replay),
+        Err(err) => {
+            println!("couldn't load replay from {}: {}", path.display(), err);
+            None
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Daily challenges: one board per game per day, the same for everyone.
+//!
+//! Results are kept per profile in a history file like this:
+//!
+//! ```text
+//! mofang-daily 1
+//! daily alice mofang 2021-06-01 183500 40
+//! ```
+//!
+//! Each `daily` line is the profile, game, date, time in milliseconds and how many combos it took.
+//! Profile names can't have spaces in them.
+
+use crate::{
+    save::{self, LoadError},
+    Game, Node, Status,
+};
+
+use std::{fmt, str::FromStr, time::Duration};
+
+/// The newest history version we know how to write.
+pub const DAILY_VERSION: u32 = 1;
+
+const HEADER: &str = "mofang-daily";
+
+/// A day on the calendar.
+/// We don't know about time zones; whoever makes one of these decides what "today" is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    /// 1 to 12.
+    pub month: u32,
+    /// 1 to however many days the month has.
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns `None` if there's no such day.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day) {
+            Some(Self { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    /// How many days after 1970-01-01 this is.
+    pub fn days_since_epoch(&self) -> i64 {
+        // From http://howardhinnant.github.io/date_algorithms.html
+        let (month, day) = (self.month as i64, self.day as i64);
+        let year = self.year as i64 - (month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The day this many days after 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = (shifted_month + 2) % 12 + 1;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// The day this many days later (or earlier, if it's negative).
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// 0 for Monday up to 6 for Sunday.
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 was a Thursday
+        (self.days_since_epoch() + 3).rem_euclid(7) as u32
+    }
+}
+
+/// How many days are in that month.
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ();
+
+    /// Reads dates written like `2021-06-01`.
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let mut parts = s.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(year)), Some(Some(month)), Some(Some(day))) => {
+                Self::new(year as i32, month, day).ok_or(())
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// The seed for this game's daily challenge on this day.
+///
+/// This is FNV-1a of the game name and date, so it's the same on every machine and every version.
+pub fn seed(game: &str, date: Date) -> u64 {
+    format!("{} {}", game, date)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// How a profile did on one daily challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyResult {
+    pub profile: String,
+    pub game: String,
+    pub date: Date,
+    pub time: Duration,
+    pub moves: u32,
+}
+
+/// Everyone's daily challenge results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DailyHistory {
+    /// Oldest first.
+    results: Vec<DailyResult>,
+}
+
+impl DailyHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn results(&self) -> &[DailyResult] {
+        &self.results
+    }
+
+    /// How this profile did on that day's challenge, if they cleared it.
+    pub fn get(&self, profile: &str, game: &str, date: Date) -> Option<&DailyResult> {
+        self.results
+            .iter()
+            .find(|r| r.profile == profile && r.game == game && r.date == date)
+    }
+
+    /// Write down a cleared daily challenge from that day.
+    ///
+    /// Only the first clear counts, so this returns false and does nothing
+    /// if the game isn't won, was won with help, or the profile already cleared that day.
+    pub fn record<N: Node + Clone>(&mut self, profile: &str, game: &Game<N>, date: Date) -> bool {
+        if game.status() != Status::Won
+            || game.assisted()
+            || self.get(profile, N::name(), date).is_some()
+        {
+            return false;
+        }
+        self.results.push(DailyResult {
+            profile: profile.to_owned(),
+            game: N::name().to_owned(),
+            date,
+            time: game.elapsed(),
+            moves: game.moves(),
+        });
+        true
+    }
+
+    /// Write the history out in the format described in the module docs.
+    pub fn write(&self) -> String {
+        let mut out = format!("{} {}\n", HEADER, DAILY_VERSION);
+        for r in &self.results {
+            out.push_str(&format!(
+                "daily {} {} {} {} {}\n",
+                r.profile,
+                r.game,
+                r.date,
+                r.time.as_millis(),
+                r.moves
+            ));
+        }
+        out
+    }
+
+    /// Read the history back out of the format described in the module docs.
+    pub fn read(text: &str) -> Result<Self, LoadError> {
+        let lines = save::lines(text, HEADER, DAILY_VERSION)?;
+
+        let mut out = Self::new();
+        for (line_no, line) in lines {
+            let malformed = || LoadError::Malformed {
+                line: line_no,
+                text: line.to_owned(),
+            };
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["daily", profile, game, date, time, moves] => out.results.push(DailyResult {
+                    profile: (*profile).to_owned(),
+                    game: (*game).to_owned(),
+                    date: date.parse().map_err(|_| malformed())?,
+                    time: Duration::from_millis(time.parse().map_err(|_| malformed())?),
+                    moves: moves.parse().map_err(|_| malformed())?,
+                }),
+                _ => return Err(malformed()),
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,314 @@
+//! Rating how hard a board is to clear.
+//!
+//! Nothing here knows the rules of any particular game; it all goes through the solver,
+//! so the same signals work for every game:
+//!
+//! - how many different ways to clear the board a search turns up,
+//! - how many combos there are to choose from as the board gets cleared,
+//! - how often picking combos at random clears the board,
+//! - how many steps have a choice of combos, but only one that doesn't lead to a dead end.
+
+use crate::{
+    solver::{self, legal_combos},
+    Board, Coordinate, GenOptions, Node, Solution, Solver,
+};
+
+use std::{collections::HashSet, fmt, hash::Hash, str::FromStr};
+
+/// How many boards `seed_in_tier` tries before giving up.
+/// Every tier turns up often enough that running out means something's wrong.
+pub const MAX_TIER_ATTEMPTS: usize = 200;
+
+/// A rough bucket for how hard a board is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 3] = [Tier::Easy, Tier::Normal, Tier::Hard];
+
+    /// Which tier a `Difficulty::score` falls in.
+    /// The cutoffs were picked so standard solvable boards of both games land in every tier.
+    pub fn of_score(score: f32) -> Self {
+        if score < 41.0 {
+            Tier::Easy
+        } else if score < 47.0 {
+            Tier::Normal
+        } else {
+            Tier::Hard
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tier::Easy => "easy",
+            Tier::Normal => "normal",
+            Tier::Hard => "hard",
+        })
+    }
+}
+
+impl FromStr for Tier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Tier::ALL
+            .iter()
+            .copied()
+            .find(|tier| tier.to_string() == s)
+            .ok_or(())
+    }
+}
+
+/// What the analyzer found out about a board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    /// How many ways to clear the board the search found, up to `Analyzer::max_solutions`.
+    pub solutions: usize,
+    /// How many legal combos there were at each step of the solver's line.
+    /// Empty if the solver couldn't clear the board.
+    pub branching: Vec<usize>,
+    /// How many of the random playouts cleared the board, from 0 to 1.
+    pub playout_wins: f32,
+    /// How many steps of the solver's line had a choice of combos but only one that wasn't a
+    /// dead end, from 0 to 1.
+    pub forced: f32,
+    /// From 0 for trivial up to 100 for brutal.
+    pub score: f32,
+    pub tier: Tier,
+}
+
+impl Difficulty {
+    /// The average number of legal combos along the solver's line.
+    pub fn mean_branching(&self) -> f32 {
+        if self.branching.is_empty() {
+            return 0.0;
+        }
+        self.branching.iter().sum::<usize>() as f32 / self.branching.len() as f32
+    }
+}
+
+/// Knobs for how hard `Analyzer::rate` looks.
+/// Bigger budgets give steadier ratings, but take longer.
+pub struct Analyzer {
+    /// Finds the line the other signals are measured along.
+    pub solver: Solver,
+    /// Stop counting solutions after this many.
+    pub max_solutions: usize,
+    /// How many board states counting solutions gets to look at.
+    pub max_states: usize,
+    /// How many games of picking combos at random to play.
+    pub playouts: u32,
+    /// How many board states to spend on each combo when checking for dead ends.
+    pub dead_end_states: usize,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self {
+            solver: Solver::default(),
+            max_solutions: 64,
+            max_states: 2_000,
+            playouts: 40,
+            dead_end_states: 200,
+        }
+    }
+}
+
+impl Analyzer {
+    /// Rate the board. The same board always gets the same rating.
+    pub fn rate<N: Node + Clone + Hash>(&self, board: &Board<N>) -> Difficulty {
+        match self.solver.solve(board) {
+            Solution::Solved(line) => self.rate_with(board, Some(&line)),
+            _ => self.rate_with(board, None),
+        }
+    }
+
+    /// Rate a board that's already known to be cleared by this line.
+    ///
+    /// Boards the solver had to repair don't always solve again from scratch,
+    /// so this is the one to use on boards fresh out of `Solver::solvable_game_with`.
+    pub fn rate_solved<N: Node + Clone + Hash>(
+        &self,
+        board: &Board<N>,
+        line: &[Vec<Coordinate>],
+    ) -> Difficulty {
+        self.rate_with(board, Some(line))
+    }
+
+    fn rate_with<N: Node + Clone + Hash>(
+        &self,
+        board: &Board<N>,
+        line: Option<&[Vec<Coordinate>]>,
+    ) -> Difficulty {
+        let solutions = self.count_solutions(board);
+        let playout_wins = self.playout_wins(board);
+
+        let (branching, forced) = match line {
+            Some(line) => self.along_line(board, line),
+            // Not even the solver could do it, so that's as forced as it gets
+            None => (Vec::new(), 1.0),
+        };
+
+        let mut difficulty = Difficulty {
+            solutions,
+            branching,
+            playout_wins,
+            forced,
+            score: 0.0,
+            tier: Tier::Easy,
+        };
+        difficulty.score = self.score(&difficulty);
+        difficulty.tier = Tier::of_score(difficulty.score);
+        difficulty
+    }
+
+    /// Squash the signals down to one number from 0 to 100.
+    fn score(&self, difficulty: &Difficulty) -> f32 {
+        // Each of these is 0 when it says easy and 1 when it says hard
+        let few_solutions =
+            1.0 - (1.0 + difficulty.solutions as f32).ln() / (1.0 + self.max_solutions as f32).ln();
+        let random_losses = 1.0 - difficulty.playout_wins;
+        let narrow = 1.0 - (difficulty.mean_branching() / 16.0).min(1.0);
+        100.0
+            * (0.2 * few_solutions + 0.4 * random_losses + 0.25 * difficulty.forced + 0.15 * narrow)
+    }
+
+    /// Count ways to clear the board, depth first, forgetting about states that went nowhere.
+    fn count_solutions<N: Node + Clone + Hash>(&self, board: &Board<N>) -> usize {
+        let mut count = Count {
+            found: 0,
+            states: 0,
+            dead: HashSet::new(),
+        };
+        self.count_from(board, &mut count);
+        count.found
+    }
+
+    /// Returns whether any solutions were found from here.
+    fn count_from<N: Node + Clone + Hash>(&self, board: &Board<N>, count: &mut Count) -> bool {
+        if board.node_count() == 0 {
+            count.found += 1;
+            return true;
+        }
+        let hash = solver::board_hash(board);
+        if count.states >= self.max_states || count.dead.contains(&hash) {
+            return false;
+        }
+        count.states += 1;
+
+        let mut any = false;
+        for combo in legal_combos(board) {
+            if count.found >= self.max_solutions {
+                return any;
+            }
+            let mut next = board.clone();
+            solver::apply_combo(&mut next, &combo);
+            any |= self.count_from(&next, count);
+        }
+        // Only a dead end if we got to look at everything
+        if !any && count.states < self.max_states {
+            count.dead.insert(hash);
+        }
+        any
+    }
+
+    /// Play random combos until the board is cleared or stuck, a bunch of times.
+    fn playout_wins<N: Node + Clone + Hash>(&self, board: &Board<N>) -> f32 {
+        if self.playouts == 0 {
+            return 0.0;
+        }
+        let rng = fastrand::Rng::with_seed(solver::board_hash(board));
+        let mut wins = 0;
+        for _ in 0..self.playouts {
+            let mut board = board.clone();
+            loop {
+                let combos = legal_combos(&board);
+                if combos.is_empty() {
+                    break;
+                }
+                solver::apply_combo(&mut board, &combos[rng.usize(..combos.len())]);
+            }
+            if board.node_count() == 0 {
+                wins += 1;
+            }
+        }
+        wins as f32 / self.playouts as f32
+    }
+
+    /// The branching factor at each step of the line, and how much of the line was forced.
+    fn along_line<N: Node + Clone + Hash>(
+        &self,
+        board: &Board<N>,
+        line: &[Vec<Coordinate>],
+    ) -> (Vec<usize>, f32) {
+        let lookahead = Solver {
+            max_states: self.dead_end_states,
+            max_repairs: self.solver.max_repairs,
+            max_attempts: self.solver.max_attempts,
+        };
+        let mut board = board.clone();
+        let mut branching = Vec::new();
+        let mut forced = 0;
+        for combo in line {
+            let combos = legal_combos(&board);
+            branching.push(combos.len());
+            let open = combos
+                .iter()
+                .filter(|other| {
+                    let mut after = board.clone();
+                    solver::apply_combo(&mut after, other);
+                    !matches!(lookahead.solve(&after), Solution::Impossible)
+                })
+                .count();
+            // With only one combo on the board there's nothing to get wrong
+            if combos.len() > 1 && open <= 1 {
+                forced += 1;
+            }
+            solver::apply_combo(&mut board, combo);
+        }
+        let forced = if line.is_empty() {
+            0.0
+        } else {
+            forced as f32 / line.len() as f32
+        };
+        (branching, forced)
+    }
+}
+
+struct Count {
+    found: usize,
+    /// How many board states have been looked at.
+    states: usize,
+    /// Boards with no way to clear them.
+    dead: HashSet<u64>,
+}
+
+/// Find a seed whose standard solvable board rates as this tier, starting from `seed`.
+///
+/// This is `Solver::solvable_game_with` with `GenOptions::tier` set,
+/// except it hands back the seed of the board it found instead of the board.
+/// That seed makes the same board with `Node::new_solvable_game`, no tier needed,
+/// so it plays, replays and shares like any other.
+/// Every try solves and rates a whole board, so this is slow; front-ends shouldn't
+/// call it in the middle of a frame.
+/// Returns `None` if none of the first `MAX_TIER_ATTEMPTS` boards are in the tier.
+pub fn seed_in_tier<N: Node + Clone + Hash>(seed: u64, tier: Tier) -> Option<u64> {
+    let solver = Solver {
+        max_attempts: MAX_TIER_ATTEMPTS,
+        ..Solver::default()
+    };
+    let options = GenOptions {
+        tier: Some(tier),
+        ..N::gen_options()
+    };
+    solver
+        .solvable_attempt(seed, &options)
+        .ok()
+        .map(|(seed, _, _)| seed)
+}
@@ -0,0 +1,349 @@
+use crate::{
+    replay::{Action, Step},
+    solver, Board, Coordinate, Node, PartialResult, Score, Solver,
+};
+
+use std::{hash::Hash, time::Duration};
+
+/// A game in progress: the board and everything the player has done to it.
+///
+/// Controllers should go through this instead of poking the board themselves,
+/// so every front-end plays by the same rules.
+pub struct Game<N: Node> {
+    board: Board<N>,
+    selected: Vec<Coordinate>,
+    history: Vec<Move<N>>,
+    /// Moves that were undone, most recently undone last.
+    undone: Vec<Move<N>>,
+    status: Status,
+    /// How many combos have been made, including redos and ones that got undone.
+    moves: u32,
+    /// How long the game has been played for, as told by `add_time`.
+    elapsed: Duration,
+    /// The seed the board was made from, if we know it.
+    seed: Option<u64>,
+    /// Everything the player did, for replays.
+    steps: Vec<Step>,
+    /// Whether the player had help, from a hint or a walkthrough.
+    assisted: bool,
+}
+
+/// One combo that was applied to the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move<N> {
+    /// Where the combo was, in the order it was clicked.
+    pub coords: Vec<Coordinate>,
+    /// What was in each slot before the combo.
+    pub before: Vec<N>,
+    /// What each slot was replaced with.
+    pub after: Vec<Option<N>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    /// There's stuff left on the board but no combo can be made.
+    /// Undoing gets you back to `Playing`.
+    Lost,
+}
+
+/// What happened when the player clicked on a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    /// Nothing changed.
+    Ignored,
+    /// The selection changed.
+    Selection,
+    /// A combo was completed and applied to the board.
+    Matched,
+}
+
+impl<N: Node + Clone> Game<N> {
+    /// Start playing on the given board.
+    pub fn new(board: Board<N>) -> Self {
+        let mut this = Self {
+            board,
+            selected: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            status: Status::Playing,
+            moves: 0,
+            elapsed: Duration::default(),
+            seed: None,
+            steps: Vec::new(),
+            assisted: false,
+        };
+        this.update_status();
+        this
+    }
+
+    /// Start playing on a board that was made from this seed,
+    /// so the seed can be shown, saved and compared against.
+    pub fn seeded(board: Board<N>, seed: u64) -> Self {
+        let mut this = Self::new(board);
+        this.seed = Some(seed);
+        this
+    }
+
+    /// Put a game back together, like from a save file.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn from_parts(
+        board: Board<N>,
+        selected: Vec<Coordinate>,
+        history: Vec<Move<N>>,
+        undone: Vec<Move<N>>,
+        moves: u32,
+        elapsed: Duration,
+        seed: Option<u64>,
+        steps: Vec<Step>,
+        assisted: bool,
+    ) -> Self {
+        let mut this = Self {
+            board,
+            selected,
+            history,
+            undone,
+            status: Status::Playing,
+            moves,
+            elapsed,
+            seed,
+            steps,
+            assisted,
+        };
+        this.update_status();
+        this
+    }
+
+    pub fn board(&self) -> &Board<N> {
+        &self.board
+    }
+    /// The slots that are currently selected, in the order they were clicked.
+    pub fn selected(&self) -> &[Coordinate] {
+        &self.selected
+    }
+    /// Every combo applied so far, oldest first.
+    pub fn history(&self) -> &[Move<N>] {
+        &self.history
+    }
+    /// Moves that were undone and can be redone, most recently undone last.
+    pub fn undone(&self) -> &[Move<N>] {
+        &self.undone
+    }
+    pub fn status(&self) -> Status {
+        self.status
+    }
+    /// How many combos have been made, including redos and ones that got undone.
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+    /// How long the game has been played for.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    /// The seed the board was made from, if we know it.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    /// Everything the player did, oldest first.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+    /// Whether the player had help, so the game shouldn't count towards any bests.
+    pub fn assisted(&self) -> bool {
+        self.assisted
+    }
+    /// Note that the player had help, like from a walkthrough.
+    /// There's no taking it back.
+    pub fn mark_assisted(&mut self) {
+        self.assisted = true;
+    }
+    /// Points, moves and time so far.
+    pub fn score(&self) -> Score {
+        Score::of(self)
+    }
+
+    /// Count some time as spent playing.
+    /// The game doesn't have a clock of its own, so controllers call this every frame or so.
+    ///
+    /// The clock stops once the game is over.
+    pub fn add_time(&mut self, time: Duration) {
+        if self.status == Status::Playing {
+            self.elapsed += time;
+        }
+    }
+
+    /// Click on the given slot, selecting or deselecting it
+    /// and applying the combo if that completes one.
+    pub fn click(&mut self, coord: Coordinate) -> Click {
+        let click = self.apply_click(coord);
+        if click != Click::Ignored {
+            self.record(Action::Click(coord));
+        }
+        click
+    }
+
+    fn apply_click(&mut self, coord: Coordinate) -> Click {
+        if self.status != Status::Playing || !self.board.in_bounds(coord) {
+            return Click::Ignored;
+        }
+
+        if let Some(idx) = self.selected.iter().position(|c| *c == coord) {
+            // Oops we know this already
+            if idx == self.selected.len() - 1 {
+                self.selected.pop();
+            } else {
+                self.selected.clear();
+            }
+            Click::Selection
+        } else if self.is_selectable(coord) {
+            self.selected.push(coord);
+            // See if we have a WOMBO COMBO
+            let combo: Vec<_> = self
+                .selected
+                .iter()
+                .flat_map(|&c| self.board.get_node(c))
+                .collect();
+            if let PartialResult::Success(after) = N::select(&combo, self.board.rules()) {
+                let before = combo.into_iter().cloned().collect();
+                let coords = std::mem::take(&mut self.selected);
+                for (update, &slot) in after.iter().zip(coords.iter()) {
+                    self.board
+                        .set_node(slot, update.clone())
+                        .expect("selected slots are on the board");
+                }
+                self.history.push(Move {
+                    coords,
+                    before,
+                    after,
+                });
+                // A new move means the old future doesn't exist anymore
+                self.undone.clear();
+                self.moves += 1;
+                self.update_status();
+                Click::Matched
+            } else {
+                Click::Selection
+            }
+        } else if self.board.get_node(coord).is_none() && !self.selected.is_empty() {
+            // Click off a piece to clear your selection
+            self.selected.clear();
+            Click::Selection
+        } else {
+            Click::Ignored
+        }
+    }
+
+    /// Take back the last move, putting back what it cleared.
+    /// Returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let last = match self.history.pop() {
+            Some(it) => it,
+            None => return false,
+        };
+        for (&slot, node) in last.coords.iter().zip(last.before.iter()) {
+            self.board
+                .set_node(slot, Some(node.clone()))
+                .expect("moves are on the board");
+        }
+        self.undone.push(last);
+        self.selected.clear();
+        self.update_status();
+        self.record(Action::Undo);
+        true
+    }
+
+    /// Play the last undone move again.
+    /// Returns whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let next = match self.undone.pop() {
+            Some(it) => it,
+            None => return false,
+        };
+        for (&slot, node) in next.coords.iter().zip(next.after.iter()) {
+            self.board
+                .set_node(slot, node.clone())
+                .expect("moves are on the board");
+        }
+        self.history.push(next);
+        self.moves += 1;
+        self.selected.clear();
+        self.update_status();
+        self.record(Action::Redo);
+        true
+    }
+
+    /// Drop the current selection.
+    pub fn clear_selection(&mut self) {
+        if !self.selected.is_empty() {
+            self.selected.clear();
+            self.record(Action::ClearSelection);
+        }
+    }
+
+    /// Could the node at this slot be added to the current selection?
+    pub fn is_selectable(&self, coord: Coordinate) -> bool {
+        if self.status != Status::Playing {
+            return false;
+        }
+        let node = match self.board.get_node(coord) {
+            Some(it) => it,
+            None => return false,
+        };
+
+        node.can_select(&self.board, &coord, &self.selected) && {
+            // check to see if this is an allowed combo
+            let potential_select: Vec<_> = self
+                .selected
+                .iter()
+                .flat_map(|c| self.board.get_node(*c))
+                .chain(Some(node))
+                .collect();
+            N::select(&potential_select, self.board.rules()).is_valid()
+        }
+    }
+
+    /// Every complete combo that could be made right now, ignoring the current selection.
+    pub fn legal_combos(&self) -> Vec<Vec<Coordinate>> {
+        if self.status != Status::Playing {
+            return Vec::new();
+        }
+        solver::legal_combos(&self.board)
+    }
+
+    fn record(&mut self, action: Action) {
+        self.steps.push(Step {
+            at: self.elapsed,
+            action,
+        });
+    }
+
+    fn update_status(&mut self) {
+        self.status = if self.board.node_count() == 0 {
+            // poggers
+            Status::Won
+        } else if !solver::any_legal_combo(&self.board) {
+            // not poggers
+            Status::Lost
+        } else {
+            Status::Playing
+        };
+    }
+}
+
+impl<N: Node + Clone + Hash> Game<N> {
+    /// Suggest a combo to make next, preferring ones that keep the board solvable.
+    /// See `Solver::hint`.
+    ///
+    /// Getting one marks the game as assisted.
+    pub fn hint(&mut self, solver: &Solver) -> Option<Vec<Coordinate>> {
+        if self.status != Status::Playing {
+            return None;
+        }
+        let hint = solver.hint(&self.board);
+        if hint.is_some() {
+            self.assisted = true;
+        }
+        hint
+    }
+}
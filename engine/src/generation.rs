@@ -0,0 +1,323 @@
+use crate::{difficulty::Tier, save::MAX_DIAMETER, Board, Mask, Node};
+
+use hex2d::{Angle, Coordinate, Direction, Spin};
+use itertools::Itertools;
+
+use std::{error::Error, fmt};
+
+/// Which version of the standard layouts `Node::new_game` and `Node::new_solvable_game` make.
+///
+/// This goes up whenever a seed would make a different board than it used to,
+/// so replays can tell they were played on a board their seed doesn't make anymore.
+pub const GENERATOR_VERSION: u32 = 1;
+
+/// How many times `layout` starts over before giving up on fitting the leftovers.
+const MAX_LAYOUT_ATTEMPTS: usize = 100;
+
+/// How to lay out a new board.
+///
+/// Each game has its standard options in `Node::gen_options`;
+/// tweak those to get small practice boards or huge marathon ones.
+#[derive(Debug, Clone)]
+pub struct GenOptions<N: Node> {
+    /// How many hexes across the board is.
+    pub diameter: i32,
+    /// Only use these hexes instead of the whole hexagon, for themed layouts.
+    /// If this is set, `diameter` is ignored.
+    pub mask: Option<Mask>,
+    /// What goes in the middle of the board, if anything.
+    pub center: Option<N>,
+    /// Up to 3 nodes that always go right next to the center, in between the spokes.
+    pub around_center: Vec<N>,
+    /// Which nodes to place, and how many of each.
+    ///
+    /// A node can be listed more than once.
+    /// The order doesn't matter except that changing it changes which board a seed makes.
+    pub bank: Vec<(N, usize)>,
+    /// Chance of filling each slot in the ring right around the center.
+    pub inner_ring_fill: f32,
+    /// Chance of filling each slot in the rest of the odd rings.
+    pub odd_ring_fill: f32,
+    /// Chance of filling each slot in the even rings.
+    pub even_ring_fill: f32,
+    /// Which variant of the rules the board is played with.
+    pub rules: N::Rules,
+    /// Only keep boards that `Analyzer::default` rates as this tier.
+    /// Rating a board needs a line that clears it, so only `Solver::solvable_game_with`
+    /// and `unmatch::generate` pay attention to this; `layout` ignores it.
+    pub tier: Option<Tier>,
+}
+
+/// Why a board couldn't be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenError {
+    /// The board has to be between 1 and `MAX_DIAMETER` hexes across.
+    BadDiameter(i32),
+    /// There are more nodes than the board has hexes.
+    TooManyNodes { nodes: usize, hexes: usize },
+    /// Only 3 nodes fit around the center.
+    CrowdedCenter(usize),
+    /// The leftovers never fit anywhere they were allowed to go,
+    /// like when the mask is in pieces or the game vetoes every spot that's left.
+    Unplaceable,
+    /// None of the boards `Solver::solvable_game_with` tried could be cleared.
+    Unsolvable { attempts: usize },
+    /// Some of the boards `Solver::solvable_game_with` tried could be cleared,
+    /// but none of those were in the tier the options asked for.
+    NotInTier { tier: Tier, attempts: usize },
+}
+
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenError::BadDiameter(diameter) => write!(
+                f,
+                "the board has to be between 1 and {} hexes across, not {}",
+                MAX_DIAMETER, diameter
+            ),
+            GenError::TooManyNodes { nodes, hexes } => write!(
+                f,
+                "can't fit {} nodes on a board with {} hexes",
+                nodes, hexes
+            ),
+            GenError::CrowdedCenter(count) => {
+                write!(f, "only 3 nodes fit around the center, not {}", count)
+            }
+            GenError::Unplaceable => write!(
+                f,
+                "couldn't fit all the nodes on the board in {} attempts",
+                MAX_LAYOUT_ATTEMPTS
+            ),
+            GenError::Unsolvable { attempts } => {
+                write!(f, "none of the {} boards tried could be cleared", attempts)
+            }
+            GenError::NotInTier { tier, attempts } => write!(
+                f,
+                "none of the {} boards tried were {} and could be cleared",
+                attempts, tier
+            ),
+        }
+    }
+}
+
+impl Error for GenError {}
+
+impl<N: Node + Clone> GenOptions<N> {
+    /// Make sure a board can be laid out with these options at all.
+    ///
+    /// `layout` and `unmatch::generate` check this first, so options that came from a player
+    /// get an error instead of a panic.
+    pub fn validate(&self) -> Result<(), GenError> {
+        let diameter = self.mask.as_ref().map_or(self.diameter, Mask::diameter);
+        if !(1..=MAX_DIAMETER).contains(&diameter) {
+            return Err(GenError::BadDiameter(diameter));
+        }
+        if self.node_count() > self.hex_count() {
+            return Err(GenError::TooManyNodes {
+                nodes: self.node_count(),
+                hexes: self.hex_count(),
+            });
+        }
+        if self.around_center.len() > 3 {
+            return Err(GenError::CrowdedCenter(self.around_center.len()));
+        }
+        Ok(())
+    }
+
+    /// Every node in the bank, one by one.
+    pub fn bank_nodes(&self) -> Vec<N> {
+        self.bank
+            .iter()
+            .flat_map(|(node, count)| vec![node.clone(); *count])
+            .collect()
+    }
+
+    /// How many nodes this puts on the board, counting the center and what's around it.
+    pub fn node_count(&self) -> usize {
+        self.bank.iter().map(|(_, count)| count).sum::<usize>()
+            + self.center.iter().count()
+            + self.around_center.len()
+    }
+
+    /// How many hexes there are on a board this size (or shape).
+    pub fn hex_count(&self) -> usize {
+        match &self.mask {
+            Some(mask) => mask.len(),
+            None => {
+                let radius = (self.diameter / 2) as usize;
+                3 * radius * (radius + 1) + 1
+            }
+        }
+    }
+
+    /// An empty board of the right size and shape, with the right rules.
+    pub fn empty_board(&self) -> Board<N> {
+        let mut board = match &self.mask {
+            Some(mask) => Board::with_mask(mask.clone()),
+            None => Board::new(self.diameter),
+        };
+        board.set_rules(self.rules.clone());
+        board
+    }
+
+    /// Set how many of this node are in the bank, replacing any counts it already had.
+    pub fn set_count(&mut self, node: N, count: usize)
+    where
+        N: PartialEq,
+    {
+        self.bank.retain(|(n, _)| *n != node);
+        if count > 0 {
+            self.bank.push((node, count));
+        }
+    }
+
+    /// Probability of filling a slot in the ring this far from the center.
+    /// (Spokes are always filled.)
+    pub fn ring_fill(&self, radius: i32) -> f32 {
+        if radius == 1 {
+            self.inner_ring_fill
+        } else if radius % 2 == 1 {
+            self.odd_ring_fill
+        } else {
+            self.even_ring_fill
+        }
+    }
+}
+
+/// Lay out a board the way the standard games do.
+///
+/// The center goes in the middle (or in with the bank, if the middle is a hole in the mask),
+/// then the bank is shuffled and placed:
+/// first along three spokes curling out from the center and around the rings
+/// according to the ring fill chances, then whatever is left is scattered randomly
+/// next to what's already there.
+///
+/// `can_place` gets to veto putting a node somewhere, like to keep Qi from clumping up.
+/// If the leftovers don't fit, we start over with the same rng, so the seed still decides the board.
+///
+/// Fails if the options don't pass `GenOptions::validate`,
+/// or with `GenError::Unplaceable` if the leftovers still don't fit after `MAX_LAYOUT_ATTEMPTS` tries.
+pub fn layout<N, P>(seed: u64, options: &GenOptions<N>, can_place: P) -> Result<Board<N>, GenError>
+where
+    N: Node + Clone,
+    P: Fn(&Board<N>, Coordinate, &N) -> bool,
+{
+    options.validate()?;
+    let rand = fastrand::Rng::with_seed(seed);
+    for _ in 0..MAX_LAYOUT_ATTEMPTS {
+        if let Some(board) = try_layout(&rand, options, &can_place) {
+            return Ok(board);
+        }
+    }
+    Err(GenError::Unplaceable)
+}
+
+/// Try to lay out a board, or return `None` if the leftovers couldn't be placed.
+fn try_layout<N, P>(
+    rand: &fastrand::Rng,
+    options: &GenOptions<N>,
+    can_place: &P,
+) -> Option<Board<N>>
+where
+    N: Node + Clone,
+    P: Fn(&Board<N>, Coordinate, &N) -> bool,
+{
+    let mut out = options.empty_board();
+    // This is more rings than the board has, but the extra ones just get skipped,
+    // and shrinking it would change which board every seed makes
+    let radius = out.diameter();
+
+    let mut bank = options.bank_nodes();
+    let origin = Coordinate::new(0, 0);
+    if let (Some(center), false) = (&options.center, out.in_bounds(origin)) {
+        bank.push(center.clone());
+    }
+    rand.shuffle(&mut bank);
+
+    if let (Some(center), true) = (&options.center, out.in_bounds(origin)) {
+        out.set_node(origin, Some(center.clone()))
+            .expect("we just checked it's in bounds");
+    }
+
+    // Without a center the first node has nothing to be next to
+    let mut empty = out.node_count() == 0;
+    let mut try_insert = |coord, node, req_neighbor| {
+        // Fail if:
+        // - there's something here
+        // - it's out of bounds
+        let failure = !out.in_bounds(coord)
+            || out.get_node(coord).is_some()
+            // - there are no neighbors and we want some
+            || (req_neighbor
+                && !empty
+                && !coord.neighbors().iter().any(|&c| out.get_node(c).is_some()))
+            // - the game doesn't want it here
+            || !can_place(&out, coord, &node);
+        if failure {
+            Some(node)
+        } else {
+            out.set_node(coord, Some(node))
+                .expect("we just checked it's in bounds");
+            empty = false;
+            None
+        }
+    };
+
+    // +1 => right
+    // -1 => left
+    let chirality = if rand.bool() { 1 } else { -1 };
+    // the non-spokes surrounding the center
+    for (idx, node) in (0..3).zip(&options.around_center) {
+        let pos = Coordinate::new(0, -chirality).rotate_around_zero(Angle::from_int(idx * 2));
+        // If it's a hole or the game doesn't want it there, put it somewhere else instead
+        if let Some(failed_to_insert) = try_insert(pos, node.clone(), false) {
+            bank.push(failed_to_insert);
+        }
+    }
+
+    'outer: for radius in 1..=radius {
+        let prob = options.ring_fill(radius);
+        // try each ring this many times
+        for _ in 0..3 {
+            let mut ring = Coordinate::new(0i32, 0)
+                .ring_iter(radius, Spin::CW(Direction::XZ))
+                .collect_vec();
+            rand.shuffle(&mut ring);
+            for coord in ring {
+                let on_spoke = (coord.x == 0 && coord.y.signum() == chirality)
+                    || (coord.y == 0 && coord.z().signum() == chirality)
+                    || (coord.z() == 0 && coord.x.signum() == chirality);
+
+                let prob = if on_spoke { 1.0 } else { prob };
+                if rand.f32() <= prob {
+                    if let Some(node) = bank.pop() {
+                        let neighbor_req = radius as f32 / ((radius - 1) as f32);
+                        if let Some(failed_to_insert) =
+                            try_insert(coord, node, rand.f32() <= neighbor_req)
+                        {
+                            bank.push(failed_to_insert);
+                        }
+                    } else {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+    }
+
+    let coord_options = Coordinate::new(0, 0).range_iter(radius).collect_vec();
+    while let Some(node) = bank.pop() {
+        // TODO: Not sure how to do this nicely since `try_insert` eats `node`,
+        // so I gave up and did it with a fold
+        let result = (0..1000).try_fold(node, |node, _| {
+            let rand_coord = coord_options[rand.usize(..coord_options.len())];
+            try_insert(rand_coord, node, true)
+        });
+        if result.is_some() {
+            // just give up and try again
+            return None;
+        }
+    }
+
+    Some(out)
+}
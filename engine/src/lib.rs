@@ -1,9 +1,26 @@
+pub mod daily;
+pub mod difficulty;
+pub mod game;
+pub mod generation;
+pub mod mask;
 pub mod nodes;
+pub mod replay;
+pub mod save;
+pub mod score;
+pub mod solution;
+pub mod solver;
+pub mod stats;
+pub mod unmatch;
+pub use game::{Click, Game, Move, Status};
+pub use generation::{GenError, GenOptions};
+pub use mask::Mask;
 pub use nodes::{Node, PartialResult};
+pub use score::Score;
+pub use solver::{Hint, Solution, Solver};
 
 use hex2d::Coordinate;
 
-use std::collections::HashMap;
+use std::{error::Error, fmt, sync::Arc};
 
 /// The hexagonal board the game is played on.
 ///
@@ -11,84 +28,335 @@ use std::collections::HashMap;
 /// Coordinates are stored as qr, with q increasing to the right
 /// and r increasing to the down-right.
 ///
-/// Members of this struct are public only for the benefit of people making games.
-/// If you are making a controller, please don't access these and instead use the methods.
+/// The slots live in a flat array, so cloning a board is just copying that
+/// and lookups don't need any hashing.
+/// The board also keeps track of how open each slot is and how many of each node it has
+/// as nodes are set, so checking whether something is selectable doesn't need to look around.
+///
+/// The shape of the board is fixed when it's made:
+/// trying to put something off the edge is an error instead of growing the board.
+/// Games can lay out new boards with `Board::builder`.
+#[derive(Clone)]
 pub struct Board<N: Node> {
-    pub nodes: HashMap<Coordinate, Option<N>>,
-    pub diameter: i32,
+    nodes: Vec<Option<N>>,
+    shape: Arc<Shape>,
+    /// `max_open_neighbors` for each slot.
+    open_runs: Vec<u8>,
+    /// How many of each node there are, by `Node::index`.
+    counts: Vec<usize>,
+    /// How many slots have something in them.
+    filled: usize,
+    diameter: i32,
+    /// Which variant of the rules this board is played with.
+    rules: N::Rules,
+}
+
+/// Tried to put a node somewhere that isn't on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds(pub Coordinate);
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}) is off the board", self.0.x, self.0.y)
+    }
+}
+
+impl Error for OutOfBounds {}
+
+/// Where each slot on a board of some shape lives in the array.
+/// Boards cloned from each other share one of these.
+struct Shape {
+    radius: i32,
+    /// The coordinate of each slot, in the same order as the nodes.
+    mask: Mask,
+    /// Slot index for each spot in the (2r+1)x(2r+1) square of qr coordinates around the center,
+    /// or `NOWHERE` for the spots that aren't in the mask.
+    index: Vec<usize>,
+    /// Slot indices of each slot's neighbors, in the same order as `Coordinate::neighbors`,
+    /// or `NOWHERE` if the neighbor is off the board.
+    neighbors: Vec<[usize; 6]>,
+}
+
+const NOWHERE: usize = usize::MAX;
+
+impl Shape {
+    fn new(mask: Mask) -> Self {
+        // The mask's diameter is big enough to hold all of it
+        let radius = mask.diameter() / 2;
+        let side = (radius * 2 + 1).max(0) as usize;
+        let mut shape = Self {
+            radius,
+            mask,
+            index: vec![NOWHERE; side * side],
+            neighbors: Vec::new(),
+        };
+        for (idx, &coord) in shape.mask.coords().iter().enumerate() {
+            let square = shape.square(coord).unwrap();
+            shape.index[square] = idx;
+        }
+        shape.neighbors = shape
+            .mask
+            .coords()
+            .iter()
+            .map(|coord| {
+                let mut out = [NOWHERE; 6];
+                for (slot, neighbor) in out.iter_mut().zip(coord.neighbors().iter()) {
+                    *slot = shape.index(*neighbor).unwrap_or(NOWHERE);
+                }
+                out
+            })
+            .collect();
+        shape
+    }
+
+    /// Where this coordinate is in the square around the center, if it's in the square at all.
+    fn square(&self, coord: Coordinate) -> Option<usize> {
+        let side = self.radius * 2 + 1;
+        let (q, r) = (coord.x + self.radius, coord.y + self.radius);
+        if (0..side).contains(&q) && (0..side).contains(&r) {
+            Some((q * side + r) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Where this coordinate's slot is in the array, if it's on the board.
+    fn index(&self, coord: Coordinate) -> Option<usize> {
+        self.square(coord)
+            .map(|square| self.index[square])
+            .filter(|&idx| idx != NOWHERE)
+    }
 }
 
 impl<N: Node> Board<N> {
-    // https://www.drking.org.uk/hexagons/misc/numbers.html
+    /// How far from the center the furthest slot could be.
     pub fn radius(&self) -> i32 {
         self.diameter / 2
     }
+    /// How many slots the board has.
     pub fn hex_count(&self) -> i32 {
-        3 * self.radius() * (self.radius() + 1) + 1
+        self.shape.mask.len() as i32
     }
+    /// How many hexes across the board is.
+    /// For boards that aren't hexagons, this is the smallest hexagon around the center that fits it.
     pub fn diameter(&self) -> i32 {
         self.diameter
     }
+    /// Which hexes this board has.
+    pub fn mask(&self) -> &Mask {
+        &self.shape.mask
+    }
+    /// Which variant of the rules this board is played with.
+    /// Saves keep these on their `rules` line.
+    pub fn rules(&self) -> &N::Rules {
+        &self.rules
+    }
+    pub fn set_rules(&mut self, rules: N::Rules) {
+        self.rules = rules;
+    }
 
-    /// Create a new empty board.
-    pub fn new(diameter: i32) -> Self {
-        let mut nodes = HashMap::new();
-        for coord in Coordinate::new(0, 0).range_iter(diameter / 2) {
-            nodes.insert(coord, None);
+    /// Start laying out a hexagonal board this many hexes across.
+    pub fn builder(diameter: i32) -> BoardBuilder<N> {
+        Self::builder_with_mask(Mask::hexagon(diameter))
+    }
+
+    /// Start laying out a board with only the hexes in the mask.
+    pub fn builder_with_mask(mask: Mask) -> BoardBuilder<N> {
+        BoardBuilder {
+            board: Self::with_mask(mask),
+            error: None,
         }
+    }
 
-        Self { nodes, diameter }
+    /// Create a new empty hexagonal board with the default rules.
+    pub fn new(diameter: i32) -> Self {
+        Self::with_mask(Mask::hexagon(diameter))
+    }
+
+    /// Create a new empty board with only the hexes in the mask, with the default rules.
+    pub fn with_mask(mask: Mask) -> Self {
+        let diameter = mask.diameter();
+        let shape = Shape::new(mask);
+        let slots = shape.mask.len();
+        Self {
+            nodes: (0..slots).map(|_| None).collect(),
+            // Everything is empty and fine
+            // (holes in the mask count as open, same as off the edge)
+            open_runs: vec![6; slots],
+            shape: Arc::new(shape),
+            counts: Vec::new(),
+            filled: 0,
+            diameter,
+            rules: N::Rules::default(),
+        }
     }
 
     /// Get the node at the given coordinate, or `None` if it's out of bounds or doesn't exist.
     pub fn get_node(&self, coord: Coordinate) -> Option<&N> {
-        self.nodes.get(&coord).map(Option::as_ref).flatten()
+        self.shape
+            .index(coord)
+            .and_then(|idx| self.nodes[idx].as_ref())
     }
     /// Check whether the given coordinate is on the grid.
     pub fn in_bounds(&self, coord: Coordinate) -> bool {
-        self.nodes.get(&coord).is_some()
+        self.shape.index(coord).is_some()
     }
     /// Set the node at the given spot. Return Some with the old value if something was clobbered.
-    pub fn set_node(&mut self, coord: Coordinate, node: Option<N>) -> Option<N> {
-        self.nodes.insert(coord, node).flatten()
+    ///
+    /// If the spot isn't on the board (off the edge or in a hole in the mask), nothing changes
+    /// and you get an error instead.
+    pub fn set_node(
+        &mut self,
+        coord: Coordinate,
+        node: Option<N>,
+    ) -> Result<Option<N>, OutOfBounds> {
+        let idx = self.shape.index(coord).ok_or(OutOfBounds(coord))?;
+        let old = std::mem::replace(&mut self.nodes[idx], node);
+
+        if let Some(old) = &old {
+            self.counts[old.index()] -= 1;
+        }
+        if let Some(new) = &self.nodes[idx] {
+            let kind = new.index();
+            if kind >= self.counts.len() {
+                self.counts.resize(kind + 1, 0);
+            }
+            self.counts[kind] += 1;
+        }
+
+        let now_filled = self.nodes[idx].is_some();
+        if old.is_some() != now_filled {
+            if now_filled {
+                self.filled += 1;
+            } else {
+                self.filled -= 1;
+            }
+            // Only the neighbors' openness changes
+            let Self {
+                shape,
+                nodes,
+                open_runs,
+                ..
+            } = self;
+            for &neighbor in shape.neighbors[idx].iter().filter(|&&n| n != NOWHERE) {
+                let mut filled = [false; 6];
+                for (out, &n) in filled.iter_mut().zip(&shape.neighbors[neighbor]) {
+                    *out = n != NOWHERE && nodes[n].is_some();
+                }
+                open_runs[neighbor] = open_run(filled) as u8;
+            }
+        }
+        Ok(old)
+    }
+    /// How many of this node are on the board?
+    pub fn count(&self, node: &N) -> usize {
+        self.counts.get(node.index()).copied().unwrap_or(0)
+    }
+    /// How many slots have something in them?
+    pub fn node_count(&self) -> usize {
+        self.filled
     }
     /// Iterator through all slots on the board in no particular order.
+    ///
+    /// (It's the same order every time for boards the same size, though,
+    /// so generators can rely on it.)
     pub fn nodes_iter(&self) -> impl Iterator<Item = (Coordinate, Option<&N>)> + '_ {
-        Coordinate::new(0, 0)
-            .range_iter(self.radius())
-            .map(move |c| (c, self.get_node(c)))
+        self.shape
+            .mask
+            .coords()
+            .iter()
+            .copied()
+            .zip(self.nodes.iter().map(Option::as_ref))
     }
 
     /// Convenience method:
     /// How many open neighbors are there around the coord?
+    ///
+    /// This is kept up to date as nodes are set, so it's cheap for anything on the board.
     pub fn max_open_neighbors(&self, at: &Coordinate) -> usize {
-        match at
-            .neighbors()
-            .iter()
-            .position(|&coord| self.get_node(coord).is_some())
-        {
-            Some(pos) => {
-                // At least one neighbor exists, iter around it
-                at.neighbors()
-                    .iter()
-                    .cycle()
-                    .skip(pos + 1)
-                    .take(6)
-                    .fold((0, 0), |(maxrun, run), &neighbor| {
-                        if self.get_node(neighbor).is_some() {
-                            (maxrun.max(run), 0)
-                        } else {
-                            (maxrun, run + 1)
-                        }
-                    })
-                    .0
+        match self.shape.index(*at) {
+            Some(idx) => self.open_runs[idx] as usize,
+            // Off the board, so do it the slow way
+            None => {
+                let mut filled = [false; 6];
+                for (out, neighbor) in filled.iter_mut().zip(at.neighbors().iter()) {
+                    *out = self.get_node(*neighbor).is_some();
+                }
+                open_run(filled)
             }
-            // Everything is empty and fine
-            None => 6,
         }
     }
 }
 
+/// Lays out a new board. Get one from `Board::builder`.
+///
+/// ```ignore
+/// let board = Board::builder(5)
+///     .node(Coordinate::new(0, 0), MofangNode::Destruction)
+///     .node(Coordinate::new(1, 0), MofangNode::Fire)
+///     .build()?;
+/// ```
+pub struct BoardBuilder<N: Node> {
+    board: Board<N>,
+    /// The first node that didn't fit, if any.
+    error: Option<OutOfBounds>,
+}
+
+impl<N: Node> BoardBuilder<N> {
+    /// Play with these rules instead of the default ones.
+    pub fn rules(mut self, rules: N::Rules) -> Self {
+        self.board.rules = rules;
+        self
+    }
+
+    /// Put a node here, replacing anything that was already there.
+    pub fn node(mut self, coord: Coordinate, node: N) -> Self {
+        if let Err(err) = self.board.set_node(coord, Some(node)) {
+            self.error.get_or_insert(err);
+        }
+        self
+    }
+
+    /// Put all of these on the board.
+    pub fn nodes<I: IntoIterator<Item = (Coordinate, N)>>(self, nodes: I) -> Self {
+        nodes
+            .into_iter()
+            .fold(self, |builder, (coord, node)| builder.node(coord, node))
+    }
+
+    /// Finish the board, or complain about the first node that was off the edge.
+    pub fn build(self) -> Result<Board<N>, OutOfBounds> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.board),
+        }
+    }
+}
+
+/// The longest run of open neighbors, going around in a circle.
+fn open_run(filled: [bool; 6]) -> usize {
+    match filled.iter().position(|&it| it) {
+        Some(pos) => {
+            // At least one neighbor exists, iter around it
+            filled
+                .iter()
+                .cycle()
+                .skip(pos + 1)
+                .take(6)
+                .fold((0, 0), |(maxrun, run), &filled| {
+                    if filled {
+                        (maxrun.max(run), 0)
+                    } else {
+                        (maxrun, run + 1)
+                    }
+                })
+                .0
+        }
+        // Everything is empty and fine
+        None => 6,
+    }
+}
+
 /// Convenient helper function for your game implementations.
 pub fn all_unique<I, E>(mut iter: I) -> bool
 where
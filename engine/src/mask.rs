@@ -0,0 +1,215 @@
+//! Which hexes are on a board, for boards that aren't plain hexagons.
+//!
+//! Masks can also be drawn in a text layout, like this ring:
+//!
+//! ```text
+//!   . . .
+//!  . . . .
+//! . . x . .
+//!  . . . .
+//!   . . .
+//! ```
+//!
+//! Each `.` is a hex. Hexes in a row are two columns apart,
+//! and each row is shifted one column from the one above it like the rows of a real board.
+//! The center of the board is marked with `o` if it's a hex, or `x` if it's a hole.
+//! Spaces are just gaps.
+
+use crate::Coordinate;
+
+use std::{collections::HashSet, error::Error, fmt};
+
+/// The set of hexes a board has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    coords: Vec<Coordinate>,
+    diameter: i32,
+}
+
+impl Mask {
+    /// The standard hexagon this many hexes across.
+    pub fn hexagon(diameter: i32) -> Self {
+        Self {
+            coords: Coordinate::new(0, 0).range_iter(diameter / 2).collect(),
+            diameter,
+        }
+    }
+
+    /// A hexagon with a hexagonal hole `hole` hexes across cut out of the middle.
+    pub fn ring(diameter: i32, hole: i32) -> Self {
+        let origin = Coordinate::new(0, 0);
+        let mut out = Self::hexagon(diameter);
+        out.coords.retain(|c| c.distance(origin) >= (hole + 1) / 2);
+        out
+    }
+
+    /// A triangle with this many hexes on each side, pointing down, as close to centered as it gets.
+    pub fn triangle(side: i32) -> Self {
+        let offset = (side - 1) / 3;
+        Self::from_coords(
+            (0..side)
+                .flat_map(|q| (0..side - q).map(move |r| Coordinate::new(q - offset, r - offset))),
+        )
+    }
+
+    /// Whatever hexes you like. Duplicates are ignored.
+    ///
+    /// The hexes are kept in the same order as the hexagon around them,
+    /// so the same hexes always make the same mask.
+    pub fn from_coords<I: IntoIterator<Item = Coordinate>>(coords: I) -> Self {
+        let origin = Coordinate::new(0, 0);
+        let wanted: Vec<_> = coords.into_iter().collect();
+        let diameter = wanted
+            .iter()
+            .map(|c| c.distance(origin) * 2 + 1)
+            .max()
+            .unwrap_or(0);
+        let mut out = Self::hexagon(diameter);
+        out.coords.retain(|c| wanted.contains(c));
+        out
+    }
+
+    /// Read a mask from the text layout described in the module docs.
+    pub fn parse(text: &str) -> Result<Self, MaskError> {
+        let mut origin = None;
+        let mut hexes = Vec::new();
+        for (row, line) in text.lines().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                let spot = (row + 1, col + 1);
+                match ch {
+                    '.' => hexes.push(spot),
+                    'o' | 'x' => {
+                        if origin.is_some() {
+                            return Err(MaskError::TwoCenters);
+                        }
+                        origin = Some(spot);
+                        if ch == 'o' {
+                            hexes.push(spot);
+                        }
+                    }
+                    _ if ch.is_whitespace() => {}
+                    _ => {
+                        return Err(MaskError::Unknown {
+                            line: spot.0,
+                            column: spot.1,
+                            found: ch,
+                        })
+                    }
+                }
+            }
+        }
+
+        let (origin_row, origin_col) = origin.ok_or(MaskError::NoCenter)?;
+        let mut coords = Vec::new();
+        for (line, column) in hexes {
+            let r = line as i32 - origin_row as i32;
+            let q2 = column as i32 - origin_col as i32 - r;
+            if q2 % 2 != 0 {
+                return Err(MaskError::Misaligned { line, column });
+            }
+            coords.push(Coordinate::new(q2 / 2, r));
+        }
+        Ok(Self::from_coords(coords))
+    }
+
+    /// This mask without one of its hexes.
+    pub fn without(&self, coord: Coordinate) -> Self {
+        let mut out = self.clone();
+        out.coords.retain(|&c| c != coord);
+        out
+    }
+
+    /// This mask without any of these hexes.
+    /// The diameter stays the same, even if that takes out the whole outside ring.
+    pub fn without_all<I: IntoIterator<Item = Coordinate>>(&self, coords: I) -> Self {
+        let gone: HashSet<_> = coords.into_iter().collect();
+        let mut out = self.clone();
+        out.coords.retain(|c| !gone.contains(c));
+        out
+    }
+
+    pub fn contains(&self, coord: Coordinate) -> bool {
+        self.coords.contains(&coord)
+    }
+    /// The hexes in the mask, in the order boards made from it store them.
+    pub fn coords(&self) -> &[Coordinate] {
+        &self.coords
+    }
+    pub fn len(&self) -> usize {
+        self.coords.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
+    /// How many hexes across the smallest hexagon around the center that holds the whole mask is.
+    /// (For `Mask::hexagon` this is just the diameter it was made with.)
+    pub fn diameter(&self) -> i32 {
+        self.diameter
+    }
+    /// A short name for the shape that's the same on every machine and every version:
+    /// `hexagon` if nothing's missing, or else FNV-1a of the hexes it has.
+    pub fn shape_name(&self) -> String {
+        if self.is_hexagon() {
+            return "hexagon".to_owned();
+        }
+        let hash = self
+            .coords
+            .iter()
+            .flat_map(|c| [c.x.to_le_bytes(), c.y.to_le_bytes()].concat())
+            .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            });
+        format!("mask-{:08x}", (hash ^ (hash >> 32)) as u32)
+    }
+    /// Is this a whole hexagon with nothing missing?
+    pub fn is_hexagon(&self) -> bool {
+        let radius = self.diameter / 2;
+        self.coords.len() as i32 == 3 * radius * (radius + 1) + 1
+            && self
+                .coords
+                .iter()
+                .all(|c| c.distance(Coordinate::new(0, 0)) <= radius)
+    }
+}
+
+/// Something wrong with a text layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskError {
+    /// There's no `o` or `x` to say where the center is.
+    NoCenter,
+    /// There's more than one `o` or `x`.
+    TwoCenters,
+    /// A hex is between two columns instead of lined up with the center.
+    Misaligned { line: usize, column: usize },
+    /// A character that doesn't mean anything in a layout.
+    Unknown {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::NoCenter => write!(f, "no `o` or `x` marking the center"),
+            MaskError::TwoCenters => write!(f, "more than one center"),
+            MaskError::Misaligned { line, column } => write!(
+                f,
+                "hex at line {} column {} isn't lined up with the center",
+                line, column
+            ),
+            MaskError::Unknown {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "didn't expect `{}` at line {} column {}",
+                found, line, column
+            ),
+        }
+    }
+}
+
+impl Error for MaskError {}
@@ -1,10 +1,24 @@
-use crate::{Board, Coordinate};
+use crate::{score, unmatch, Board, Coordinate, GenError, GenOptions, Move, Solver};
+
+use std::hash::Hash;
 
 pub trait Node: Sized {
+    /// Knobs for playing variants of the game.
+    /// Every board carries its own copy, so `can_select` can find them on the board.
+    /// Games without any variants can use `()`.
+    /// They have to be `Send` so boards can be made on another thread.
+    type Rules: Clone + Default + Send;
+
     /// What game are these nodes for?
     fn name() -> &'static str;
     /// What texture does this node have?
     fn texture_name(&self) -> &'static str;
+    /// A small number that's different for each kind of node, starting from 0.
+    /// Boards keep their node counts in a list by this.
+    fn index(&self) -> usize;
+    /// Which node has this texture name?
+    /// This is the inverse of `texture_name`, used to read nodes back out of save files.
+    fn from_texture_name(name: &str) -> Option<Self>;
     /// Can the node at this position be selected?
     fn can_select(
         &self,
@@ -13,17 +27,97 @@ pub trait Node: Sized {
         selected: &[Coordinate],
     ) -> bool;
 
-    /// Given a list of Nodes, see whether this pattern could exist
+    /// Given a list of Nodes, see whether this pattern could exist under these rules
     /// and, if so, what to replace each Node with.
-    fn select(nodes: &[&Self]) -> PartialResult<Vec<Option<Self>>>;
+    fn select(nodes: &[&Self], rules: &Self::Rules) -> PartialResult<Vec<Option<Self>>>;
+
+    /// The rules as `key=value` words, for the `rules` line in save files.
+    ///
+    /// Games without any variants don't have any words, and don't get a `rules` line.
+    fn write_rules(_rules: &Self::Rules) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Read the rules back out of the words `write_rules` makes.
+    /// Keys that are left out keep their default.
+    /// Returns `None` if any of the words don't make sense.
+    fn read_rules(words: &[&str]) -> Option<Self::Rules> {
+        if words.is_empty() {
+            Some(Self::Rules::default())
+        } else {
+            None
+        }
+    }
+
+    /// How many points this combo is worth.
+    ///
+    /// By default, everything cleared off the board is worth `score::POINTS_PER_NODE`.
+    fn points(mov: &Move<Self>) -> u32 {
+        score::cleared_points(mov)
+    }
 
-    /// Create a new game with the given seed.
-    fn new_game(seed: u64) -> Board<Self>;
+    /// The options a standard game is generated with.
+    fn gen_options() -> GenOptions<Self>;
+
+    /// Create a new game with the given seed, laid out according to the options.
+    ///
+    /// Fails if the options can't make a board, like if they have more nodes than hexes.
+    fn new_game_with(seed: u64, options: &GenOptions<Self>) -> Result<Board<Self>, GenError>;
+
+    /// Create a new standard game with the given seed.
+    fn new_game(seed: u64) -> Result<Board<Self>, GenError> {
+        Self::new_game_with(seed, &Self::gen_options())
+    }
 
     /// Create a new random game.
-    fn new_game_random() -> Board<Self> {
+    fn new_game_random() -> Result<Board<Self>, GenError> {
         Node::new_game(fastrand::u64(..))
     }
+
+    /// Create a new game with the given seed that the solver has proven can be cleared.
+    ///
+    /// Returns `None` if `Solver::default` runs out of attempts,
+    /// which the standard layouts don't come anywhere near.
+    fn new_solvable_game(seed: u64) -> Option<Board<Self>>
+    where
+        Self: Clone + Hash,
+    {
+        Solver::default()
+            .solvable_game(seed)
+            .ok()
+            .map(|(board, _)| board)
+    }
+
+    /// Create a new random game that the solver has proven can be cleared.
+    fn new_solvable_game_random() -> Option<Board<Self>>
+    where
+        Self: Clone + Hash,
+    {
+        Node::new_solvable_game(fastrand::u64(..))
+    }
+
+    /// Create a new game with the given seed by playing it backwards,
+    /// along with a line of combos that clears it.
+    ///
+    /// This is instant, unlike `new_solvable_game`,
+    /// but the boards don't have the spokes and rings of the standard layout.
+    ///
+    /// Panics if `unmatch::generate` fails, which it doesn't for the standard options.
+    fn new_certified_game(seed: u64) -> (Board<Self>, Vec<Vec<Coordinate>>)
+    where
+        Self: Clone + Hash + PartialEq,
+    {
+        unmatch::generate(seed, &Self::gen_options())
+            .unwrap_or_else(|err| panic!("{} seed {}: {}", Self::name(), seed, err))
+    }
+
+    /// Create a new random game by playing it backwards, along with a line that clears it.
+    fn new_certified_game_random() -> (Board<Self>, Vec<Vec<Coordinate>>)
+    where
+        Self: Clone + Hash + PartialEq,
+    {
+        Node::new_certified_game(fastrand::u64(..))
+    }
 }
 
 /// Represents a success, failure, or needs-more-info.
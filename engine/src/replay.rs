@@ -0,0 +1,384 @@
+//! Recordings of games, so someone else can watch how a board was cleared.
+//!
+//! A replay file looks like this:
+//!
+//! ```text
+//! mofang-replay 2
+//! game mofang
+//! seed 1234
+//! generator 1
+//! diameter 11
+//! node 0 0 qi
+//! node 1 0 fire
+//! step 1500 click 0 0
+//! step 2250 click 3 -1
+//! step 4000 undo
+//! step 4600 redo
+//! ```
+//!
+//! Each `step` is when it happened, in milliseconds of play time, and what the player did.
+//! Clicks give the coordinate that was clicked; only clicks that did something are kept.
+//! The starting board is written with the same `rules`, `diameter`, `hole` and `node` lines
+//! as a save, so it can be watched without knowing how the seed was turned into a board.
+//! Replays from before version 2 don't have those, and start on the board the seed makes.
+//! The `generator` is the `GENERATOR_VERSION` the seed was turned into a board with,
+//! which is 1 for replays that don't say.
+//!
+//! Replays are easy to edit, so anything that takes them on trust (like a leaderboard)
+//! should check them with `Replay::verify` or `Replay::verify_on` first.
+
+use crate::{
+    generation::GENERATOR_VERSION,
+    save::{self, BoardReader, LoadError},
+    Board, Click, Coordinate, Game, Move, Node, Score, Status,
+};
+
+use std::{error::Error, fmt, hash::Hash, time::Duration};
+
+/// The newest replay version we know how to write.
+///
+/// Version 2 added the starting board and the generator version.
+pub const REPLAY_VERSION: u32 = 2;
+
+const HEADER: &str = "mofang-replay";
+
+/// Something the player did to the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Click(Coordinate),
+    Undo,
+    Redo,
+    /// Dropped the whole selection without clicking anything.
+    ClearSelection,
+}
+
+impl Action {
+    /// Do this to the game, like the player did.
+    /// Returns whether it changed anything.
+    pub fn apply<N: Node + Clone>(self, game: &mut Game<N>) -> bool {
+        match self {
+            Action::Click(coord) => game.click(coord) != Click::Ignored,
+            Action::Undo => game.undo(),
+            Action::Redo => game.redo(),
+            Action::ClearSelection => {
+                let any = !game.selected().is_empty();
+                game.clear_selection();
+                any
+            }
+        }
+    }
+}
+
+/// One action and when it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// How much play time had passed.
+    pub at: Duration,
+    pub action: Action,
+}
+
+impl fmt::Display for Step {
+    /// Writes the step like it is after `step` in the module docs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.at.as_millis())?;
+        match self.action {
+            Action::Click(coord) => write!(f, "click {} {}", coord.x, coord.y),
+            Action::Undo => write!(f, "undo"),
+            Action::Redo => write!(f, "redo"),
+            Action::ClearSelection => write!(f, "deselect"),
+        }
+    }
+}
+
+/// Read a step back out of the words after `step`.
+pub(crate) fn parse_step(words: &[&str]) -> Option<Step> {
+    let (millis, action) = words.split_first()?;
+    let action = match action {
+        ["click", x, y] => Action::Click(Coordinate::new(x.parse().ok()?, y.parse().ok()?)),
+        ["undo"] => Action::Undo,
+        ["redo"] => Action::Redo,
+        ["deselect"] => Action::ClearSelection,
+        _ => return None,
+    };
+    Some(Step {
+        at: Duration::from_millis(millis.parse().ok()?),
+        action,
+    })
+}
+
+/// Why a replay didn't pass `Replay::verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invalid {
+    /// The replay is for a different game than the one it was checked as.
+    WrongGame(String),
+    /// The replay is on a different seed than the one it was supposed to be.
+    WrongSeed { expected: u64, found: u64 },
+    /// The replay's seed was made into a board by a different version of the generator,
+    /// so it doesn't make the same board anymore.
+    WrongGenerator { expected: u32, found: u32 },
+    /// The replay starts on a different board than the one its seed makes.
+    WrongBoard,
+    /// The seed didn't make a board the solver could clear, so there's nothing to check against.
+    NoBoard,
+    /// This step happened before the one before it.
+    OutOfOrder { index: usize, step: Step },
+    /// This step couldn't have been done: the node wasn't selectable,
+    /// there was nothing to undo or redo, or the game was already over.
+    Illegal { index: usize, step: Step },
+    /// Everything was legal, but this many nodes were left on the board at the end.
+    NotCleared { left: usize },
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Invalid::WrongGame(name) => write!(f, "replay is for the wrong game `{}`", name),
+            Invalid::WrongSeed { expected, found } => {
+                write!(f, "replay is on seed {} instead of {}", found, expected)
+            }
+            Invalid::WrongGenerator { expected, found } => write!(
+                f,
+                "replay is from generator version {} instead of {}",
+                found, expected
+            ),
+            Invalid::WrongBoard => write!(f, "replay doesn't start on its seed's board"),
+            Invalid::NoBoard => write!(f, "replay's seed didn't make a board"),
+            Invalid::OutOfOrder { index, step } => {
+                write!(f, "step {} (`{}`) goes back in time", index + 1, step)
+            }
+            Invalid::Illegal { index, step } => {
+                write!(f, "step {} (`{}`) isn't a legal move", index + 1, step)
+            }
+            Invalid::NotCleared { left } => write!(f, "{} nodes were left on the board", left),
+        }
+    }
+}
+
+impl Error for Invalid {}
+
+/// A whole game, from the seed to the last thing the player did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// The `Node::name` of the game.
+    pub game: String,
+    pub seed: u64,
+    /// The `GENERATOR_VERSION` that made the seed into a board.
+    pub generator: u32,
+    /// The board it starts on, as the lines a save would have for it.
+    /// This is empty for replays from before version 2.
+    pub board: Vec<String>,
+    /// Oldest first.
+    pub steps: Vec<Step>,
+}
+
+impl Replay {
+    /// Everything done in this game so far.
+    ///
+    /// Returns `None` if we don't know the seed, since then there's nothing to check it against,
+    /// or if the steps don't add up to the game's history,
+    /// like for a game resumed from a save from before steps were kept.
+    pub fn of<N: Node + Clone>(game: &Game<N>) -> Option<Self> {
+        let seed = game.seed()?;
+
+        // Take the history back off to find the board it started on
+        let mut start = game.board().clone();
+        for mov in game.history().iter().rev() {
+            for (&coord, before) in mov.coords.iter().zip(&mov.before) {
+                start.set_node(coord, Some(before.clone())).ok()?;
+            }
+        }
+        // Then make sure the steps really get from there to here
+        let mut again = Game::new(start.clone());
+        for step in game.steps() {
+            step.action.apply(&mut again);
+        }
+        let same = |a: &[Move<N>], b: &[Move<N>]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| save::same_move(a, b))
+        };
+        if !same(again.history(), game.history())
+            || !same(again.undone(), game.undone())
+            || again.selected() != game.selected()
+        {
+            return None;
+        }
+
+        Some(Self {
+            game: N::name().to_owned(),
+            seed,
+            generator: GENERATOR_VERSION,
+            board: save::board_lines(&start),
+            steps: game.steps().to_vec(),
+        })
+    }
+
+    /// The board the replay starts on.
+    ///
+    /// Malformed lines are numbered from the first line of the board.
+    /// Replays without a board start on the one the seed makes, like `Node::new_solvable_game`,
+    /// or fail with `LoadError::NoBoard` if it doesn't make one.
+    pub fn board<N: Node + Clone + Hash>(&self) -> Result<Board<N>, LoadError> {
+        match self.written_board()? {
+            Some(board) => Ok(board),
+            None => N::new_solvable_game(self.seed).ok_or(LoadError::NoBoard(self.seed)),
+        }
+    }
+
+    /// The board written down in the replay, or `None` for replays from before version 2.
+    fn written_board<N: Node + Clone>(&self) -> Result<Option<Board<N>>, LoadError> {
+        if self.game != N::name() {
+            return Err(LoadError::WrongGame(self.game.clone()));
+        }
+        if self.board.is_empty() {
+            return Ok(None);
+        }
+
+        let mut reader = BoardReader::new();
+        for (idx, line) in self.board.iter().enumerate() {
+            let malformed = || LoadError::Malformed {
+                line: idx + 1,
+                text: line.clone(),
+            };
+            let mut words = line.split_whitespace();
+            let key = words.next().ok_or_else(malformed)?;
+            let args: Vec<_> = words.collect();
+            if !reader.read_line(key, &args, malformed)? {
+                return Err(malformed());
+            }
+        }
+        reader.finish().map(Some).ok_or(LoadError::NotASave)
+    }
+
+    /// Play the whole replay back on a fresh board to make sure it's a real clear of that seed.
+    ///
+    /// This makes the seed's board with `Node::new_solvable_game`, which runs the solver;
+    /// anything checking lots of replays on the same seed (like a leaderboard)
+    /// should make the board once and use `verify_on` instead.
+    pub fn verify<N: Node + Clone + Hash>(&self, seed: u64) -> Result<Score, Invalid> {
+        if self.game != N::name() {
+            return Err(Invalid::WrongGame(self.game.clone()));
+        }
+        if self.seed != seed {
+            return Err(Invalid::WrongSeed {
+                expected: seed,
+                found: self.seed,
+            });
+        }
+        if self.generator != GENERATOR_VERSION {
+            return Err(Invalid::WrongGenerator {
+                expected: GENERATOR_VERSION,
+                found: self.generator,
+            });
+        }
+        let board = N::new_solvable_game(seed).ok_or(Invalid::NoBoard)?;
+        self.verify_on(&board)
+    }
+
+    /// Play the whole replay back on this board, which should be the one its seed makes,
+    /// to make sure it's a real clear.
+    ///
+    /// The board in the replay has to be this one,
+    /// and every step has to do something, going through the same `can_select` and `select` checks
+    /// as playing it by hand, and the board has to be empty at the end.
+    /// Returns the score of the run, timed by when its steps happened.
+    ///
+    /// The timing is only as honest as whoever wrote the file:
+    /// steps that go back in time are caught (`Invalid::OutOfOrder`),
+    /// but nothing stops an edited replay from squeezing its steps closer together,
+    /// so a fast time here isn't proof anyone played that fast.
+    pub fn verify_on<N: Node + Clone>(&self, board: &Board<N>) -> Result<Score, Invalid> {
+        if self.game != N::name() {
+            return Err(Invalid::WrongGame(self.game.clone()));
+        }
+        // A board we can't read can't be this one
+        match self.written_board::<N>() {
+            Ok(Some(written)) if !save::same_board(&written, board) => {
+                return Err(Invalid::WrongBoard)
+            }
+            Err(_) => return Err(Invalid::WrongBoard),
+            _ => {}
+        }
+
+        let mut game = Game::seeded(board.clone(), self.seed);
+        let mut last = Duration::default();
+        for (index, &step) in self.steps.iter().enumerate() {
+            if step.at < last {
+                return Err(Invalid::OutOfOrder { index, step });
+            }
+            game.add_time(step.at - last);
+            last = step.at;
+            if !step.action.apply(&mut game) {
+                return Err(Invalid::Illegal { index, step });
+            }
+        }
+
+        match game.status() {
+            Status::Won => Ok(game.score()),
+            _ => Err(Invalid::NotCleared {
+                left: game.board().node_count(),
+            }),
+        }
+    }
+
+    /// How long the recording is.
+    pub fn length(&self) -> Duration {
+        self.steps
+            .last()
+            .map_or(Duration::default(), |step| step.at)
+    }
+
+    /// Write the replay out in the format described in the module docs.
+    pub fn write(&self) -> String {
+        let mut out = format!(
+            "{} {}\ngame {}\nseed {}\ngenerator {}\n",
+            HEADER, REPLAY_VERSION, self.game, self.seed, self.generator
+        );
+        for line in &self.board {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for step in &self.steps {
+            out.push_str(&format!("step {}\n", step));
+        }
+        out
+    }
+
+    /// Read a replay back out of the format described in the module docs.
+    pub fn read(text: &str) -> Result<Self, LoadError> {
+        let lines = save::lines(text, HEADER, REPLAY_VERSION)?;
+
+        let mut game = None;
+        let mut seed = None;
+        // The only one there was before replays said
+        let mut generator = 1;
+        let mut board = Vec::new();
+        let mut steps = Vec::new();
+        for (line_no, line) in lines {
+            let malformed = || LoadError::Malformed {
+                line: line_no,
+                text: line.to_owned(),
+            };
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["game", name] => game = Some((*name).to_owned()),
+                ["seed", number] => seed = Some(number.parse().map_err(|_| malformed())?),
+                ["generator", version] => generator = version.parse().map_err(|_| malformed())?,
+                // Checked once we know which game it's for, in `board`
+                ["rules", ..] | ["diameter", ..] | ["hole", ..] | ["node", ..] => {
+                    board.push(line.to_owned())
+                }
+                ["step", words @ ..] => steps.push(parse_step(words).ok_or_else(malformed)?),
+                _ => return Err(malformed()),
+            }
+        }
+
+        match (game, seed) {
+            (Some(game), Some(seed)) => Ok(Self {
+                game,
+                seed,
+                generator,
+                board,
+                steps,
+            }),
+            _ => Err(LoadError::NotASave),
+        }
+    }
+}
@@ -0,0 +1,521 @@
+//! Reading and writing games in a plain-text save format.
+//!
+//! A save looks like this:
+//!
+//! ```text
+//! mofang-save 7
+//! game mofang
+//! seed 1234
+//! rules qi_matches_elements=false celestial_matches=cancel freeness=3 qi_freeness=5 human_freeness=2
+//! diameter 23
+//! hole 0 0
+//! node 0 0 destruction
+//! node 1 -1 qi
+//! moves 2
+//! time 93250
+//! assisted
+//! selected 1 -1
+//! move 3 0 fire - 4 0 metal -
+//! undone -1 2 yin creation 2 1 yang creation
+//! step 1500 click 3 0
+//! step 2250 click 4 0
+//! ```
+//!
+//! The `rules` are whatever `Node::write_rules` says; games without variants leave them out,
+//! and so do saves from before version 6, which get the default rules.
+//! The seed is left out if the board wasn't made from one.
+//! Boards that aren't whole hexagons list the spots missing from the hexagon as `hole`s,
+//! right after the diameter.
+//! Nodes are written with their `Node::texture_name`.
+//! Each `move` and `undone` line is a list of `x y before after` groups,
+//! one per slot in the order they were clicked, with `-` for an empty slot.
+//! Moves are listed oldest first; undone moves are listed in the order they were undone.
+//! `moves` is how many combos were made in total, and `time` is how long was spent playing in milliseconds.
+//! `assisted` is only there if the player had a hint or a walkthrough.
+//! The `step`s are everything the player did, written like in `replay` files.
+//!
+//! The history, undone moves and selection are checked against the board when a save is read,
+//! so a save can't make moves that never happened.
+
+use crate::{replay, Board, Coordinate, Game, Mask, Move, Node, OutOfBounds};
+
+use std::{collections::HashSet, error::Error, fmt, fs, io, path::Path, time::Duration};
+
+/// The newest save version we know how to write.
+/// Anything this version or older can be loaded.
+///
+/// Version 2 added holes, version 3 added the move count and time, version 4 added the seed,
+/// version 5 added the steps, version 6 added the rules, and version 7 added `assisted`.
+pub const SAVE_VERSION: u32 = 7;
+
+const HEADER: &str = "mofang-save";
+
+/// The biggest board a save can have, so a bad save can't ask for all the memory.
+pub const MAX_DIAMETER: i32 = 255;
+
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// This doesn't look like a save file at all.
+    NotASave,
+    /// The save was written by a newer version than we understand.
+    UnsupportedVersion(u32),
+    /// The save is for a different game than the one we're loading.
+    WrongGame(String),
+    /// This line couldn't be understood.
+    Malformed { line: usize, text: String },
+    /// A node name that the game doesn't know about.
+    UnknownNode(String),
+    /// A coordinate that isn't on the board.
+    OutOfBounds(Coordinate),
+    /// This line is fine on its own, but couldn't have happened on the board in the save.
+    Inconsistent { line: usize },
+    /// There's no board written down, and the seed didn't make one the solver could clear.
+    NoBoard(u64),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "couldn't read save: {}", err),
+            LoadError::NotASave => write!(f, "not a save file"),
+            LoadError::UnsupportedVersion(v) => write!(
+                f,
+                "save version {} is newer than the newest supported version {}",
+                v, SAVE_VERSION
+            ),
+            LoadError::WrongGame(name) => write!(f, "save is for the wrong game `{}`", name),
+            LoadError::Malformed { line, text } => {
+                write!(f, "couldn't understand line {}: `{}`", line, text)
+            }
+            LoadError::UnknownNode(name) => write!(f, "unknown node `{}`", name),
+            LoadError::OutOfBounds(c) => write!(f, "({}, {}) is off the board", c.x, c.y),
+            LoadError::Inconsistent { line } => {
+                write!(f, "line {} doesn't match the rest of the board", line)
+            }
+            LoadError::NoBoard(seed) => write!(f, "seed {} didn't make a board", seed),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<OutOfBounds> for LoadError {
+    fn from(OutOfBounds(coord): OutOfBounds) -> Self {
+        LoadError::OutOfBounds(coord)
+    }
+}
+
+/// Write the game out in the save format.
+pub fn write<N: Node + Clone>(game: &Game<N>) -> String {
+    let mut out = format!("{} {}\ngame {}\n", HEADER, SAVE_VERSION, N::name());
+    if let Some(seed) = game.seed() {
+        out.push_str(&format!("seed {}\n", seed));
+    }
+    for line in board_lines(game.board()) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "moves {}\ntime {}\n",
+        game.moves(),
+        game.elapsed().as_millis()
+    ));
+    if game.assisted() {
+        out.push_str("assisted\n");
+    }
+    for coord in game.selected() {
+        out.push_str(&format!("selected {} {}\n", coord.x, coord.y));
+    }
+    for (key, moves) in &[("move", game.history()), ("undone", game.undone())] {
+        for mov in moves.iter() {
+            out.push_str(&format!("{}{}\n", key, write_move(mov)));
+        }
+    }
+    for step in game.steps() {
+        out.push_str(&format!("step {}\n", step));
+    }
+    out
+}
+
+/// The lines that make up the board itself: its rules, size, holes and nodes.
+/// Replays keep their starting board the same way.
+pub(crate) fn board_lines<N: Node>(board: &Board<N>) -> Vec<String> {
+    let mut out = Vec::new();
+    let rules = N::write_rules(board.rules());
+    if !rules.is_empty() {
+        out.push(format!("rules {}", rules.join(" ")));
+    }
+    out.push(format!("diameter {}", board.diameter()));
+    for coord in Coordinate::new(0, 0).range_iter(board.radius()) {
+        if !board.in_bounds(coord) {
+            out.push(format!("hole {} {}", coord.x, coord.y));
+        }
+    }
+    for (coord, node) in board.nodes_iter() {
+        if let Some(node) = node {
+            out.push(format!(
+                "node {} {} {}",
+                coord.x,
+                coord.y,
+                node.texture_name()
+            ));
+        }
+    }
+    out
+}
+
+/// Builds a board back up out of the lines `board_lines` writes, one at a time.
+pub(crate) struct BoardReader<N: Node> {
+    rules: Option<N::Rules>,
+    /// The `diameter` and the `hole`s so far, until the board gets laid out from them.
+    /// Laying it out is the slow part, so it only happens once.
+    shape: Option<(i32, HashSet<Coordinate>)>,
+    /// Everything so far, once something needs the board.
+    board: Option<Board<N>>,
+}
+
+impl<N: Node + Clone> BoardReader<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            rules: None,
+            shape: None,
+            board: None,
+        }
+    }
+
+    /// The board so far, laid out from the `diameter` and `hole`s the first time it's needed.
+    pub(crate) fn board(&mut self) -> Option<&mut Board<N>> {
+        if let Some((diameter, holes)) = self.shape.take() {
+            let mask = Mask::hexagon(diameter).without_all(holes);
+            self.board = Some(Board::with_mask(mask));
+        }
+        self.board.as_mut()
+    }
+
+    /// Take in this line if it's part of the board, and say whether it was.
+    pub(crate) fn read_line(
+        &mut self,
+        key: &str,
+        args: &[&str],
+        malformed: impl Fn() -> LoadError + Copy,
+    ) -> Result<bool, LoadError> {
+        match (key, args) {
+            ("rules", words) => self.rules = Some(N::read_rules(words).ok_or_else(malformed)?),
+            ("diameter", [diameter]) => {
+                // A second one would throw away everything before it
+                if self.shape.is_some() || self.board.is_some() {
+                    return Err(malformed());
+                }
+                let diameter = diameter.parse().map_err(|_| malformed())?;
+                if !(1..=MAX_DIAMETER).contains(&diameter) {
+                    return Err(malformed());
+                }
+                self.shape = Some((diameter, HashSet::new()));
+            }
+            ("hole", [x, y]) => {
+                // Holes have to come before anything is put on the board
+                let (diameter, holes) = self.shape.as_mut().ok_or_else(malformed)?;
+                let coord = Coordinate::new(
+                    x.parse().map_err(|_| malformed())?,
+                    y.parse().map_err(|_| malformed())?,
+                );
+                // Same as a hole that was already made, it's not on the board
+                let on_board = coord.distance(Coordinate::new(0, 0)) <= *diameter / 2;
+                if !on_board || !holes.insert(coord) {
+                    return Err(LoadError::OutOfBounds(coord));
+                }
+            }
+            ("node", [x, y, name]) => {
+                let board = self.board().ok_or_else(malformed)?;
+                let coord = parse_coord(board, x, y).ok_or_else(malformed)??;
+                board.set_node(coord, Some(parse_node(name)?))?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The board with its rules, if there was one.
+    pub(crate) fn finish(mut self) -> Option<Board<N>> {
+        self.board();
+        let mut board = self.board?;
+        if let Some(rules) = self.rules {
+            board.set_rules(rules);
+        }
+        Some(board)
+    }
+}
+
+/// The lines after the header of one of our text formats, numbered from 1,
+/// skipping blank lines and `#` comments.
+///
+/// The header is `<header> <version>`, and anything newer than `newest` is refused.
+/// Stats, replays and the rest share this, so they all read the same way saves do.
+pub(crate) fn lines<'a>(
+    text: &'a str,
+    header: &str,
+    newest: u32,
+) -> Result<impl Iterator<Item = (usize, &'a str)>, LoadError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let version = match lines
+        .next()
+        .map(|(_, l)| l.split_whitespace().collect::<Vec<_>>())
+    {
+        Some(words) if words.len() == 2 && words[0] == header => {
+            words[1].parse::<u32>().map_err(|_| LoadError::NotASave)?
+        }
+        _ => return Err(LoadError::NotASave),
+    };
+    if version > newest {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+    Ok(lines)
+}
+
+/// Read a game back out of the save format.
+pub fn read<N: Node + Clone>(text: &str) -> Result<Game<N>, LoadError> {
+    let lines = lines(text, HEADER, SAVE_VERSION)?;
+
+    let mut game_named = false;
+    let mut reader = BoardReader::new();
+    let mut selected = Vec::new();
+    let mut history = Vec::new();
+    let mut undone = Vec::new();
+    let mut moves = None;
+    let mut seed = None;
+    let mut elapsed = Duration::default();
+    let mut assisted = false;
+    let mut steps = Vec::new();
+    for (line_no, line) in lines {
+        let malformed = || LoadError::Malformed {
+            line: line_no,
+            text: line.to_owned(),
+        };
+        let mut words = line.split_whitespace();
+        let key = words.next().ok_or_else(malformed)?;
+        let args: Vec<_> = words.collect();
+
+        if reader.read_line(key, &args, malformed)? {
+            continue;
+        }
+        match (key, args.as_slice()) {
+            ("game", [name]) => {
+                if *name != N::name() {
+                    return Err(LoadError::WrongGame((*name).to_owned()));
+                }
+                game_named = true;
+            }
+            ("seed", [number]) => seed = Some(number.parse().map_err(|_| malformed())?),
+            ("moves", [count]) => moves = Some(count.parse().map_err(|_| malformed())?),
+            ("time", [millis]) => {
+                elapsed = Duration::from_millis(millis.parse().map_err(|_| malformed())?);
+            }
+            ("assisted", []) => assisted = true,
+            ("selected", [x, y]) => {
+                let board = reader.board().ok_or_else(malformed)?;
+                selected.push((line_no, parse_coord(board, x, y).ok_or_else(malformed)??));
+            }
+            ("move", groups) | ("undone", groups) => {
+                let board = reader.board().ok_or_else(malformed)?;
+                let mov = parse_move(groups).ok_or_else(malformed)??;
+                if let Some(&coord) = mov.coords.iter().find(|c| !board.in_bounds(**c)) {
+                    return Err(LoadError::OutOfBounds(coord));
+                }
+                if key == "move" {
+                    history.push((line_no, mov));
+                } else {
+                    undone.push((line_no, mov));
+                }
+            }
+            ("step", words) => steps.push(replay::parse_step(words).ok_or_else(malformed)?),
+            _ => return Err(malformed()),
+        }
+    }
+
+    // The history gets checked with the rules it was played by, which `finish` puts on the board
+    let board = match reader.finish() {
+        Some(board) if game_named => board,
+        _ => return Err(LoadError::NotASave),
+    };
+    check_history(&board, &history, &undone, &selected)?;
+    let lines = |moves: Vec<(usize, Move<N>)>| moves.into_iter().map(|(_, mov)| mov).collect();
+    let (history, undone): (Vec<_>, Vec<_>) = (lines(history), lines(undone));
+    let selected = selected.into_iter().map(|(_, coord)| coord).collect();
+    // Older saves didn't count, so the history is the best guess
+    let moves = moves.unwrap_or(history.len() as u32);
+    Ok(Game::from_parts(
+        board, selected, history, undone, moves, elapsed, seed, steps, assisted,
+    ))
+}
+
+/// Make sure the moves in a save could really have been made, in order, to end up on this board,
+/// and that the undone moves and the selection could really be redone and clicked from here.
+/// Each move and coordinate comes with the line it was on.
+fn check_history<N: Node + Clone>(
+    board: &Board<N>,
+    history: &[(usize, Move<N>)],
+    undone: &[(usize, Move<N>)],
+    selected: &[(usize, Coordinate)],
+) -> Result<(), LoadError> {
+    let name = |node: Option<&N>| node.map(Node::texture_name);
+
+    // Take the moves back off to find the board they started on
+    let mut start = board.clone();
+    for (line, mov) in history.iter().rev() {
+        for ((&coord, before), after) in mov.coords.iter().zip(&mov.before).zip(&mov.after) {
+            if name(start.get_node(coord)) != name(after.as_ref()) {
+                return Err(LoadError::Inconsistent { line: *line });
+            }
+            start.set_node(coord, Some(before.clone()))?;
+        }
+    }
+
+    // Then play them all forwards again, through the rules
+    let mut game = Game::new(start);
+    let play = |game: &mut Game<N>, line: usize, mov: &Move<N>| {
+        let made = game.history().len();
+        for &coord in &mov.coords {
+            game.click(coord);
+        }
+        match game.history().get(made) {
+            Some(played) if game.history().len() == made + 1 && same_move(played, mov) => Ok(()),
+            _ => Err(LoadError::Inconsistent { line }),
+        }
+    };
+    for (line, mov) in history {
+        play(&mut game, *line, mov)?;
+    }
+
+    for (line, coord) in selected {
+        game.click(*coord);
+        if game.selected().last() != Some(coord) {
+            return Err(LoadError::Inconsistent { line: *line });
+        }
+    }
+    game.clear_selection();
+
+    // Undone moves are redone last-undone first
+    for (line, mov) in undone.iter().rev() {
+        play(&mut game, *line, mov)?;
+    }
+    Ok(())
+}
+
+/// Whether the moves are the same, without needing `N: PartialEq`.
+pub(crate) fn same_move<N: Node>(a: &Move<N>, b: &Move<N>) -> bool {
+    let after = |mov: &Move<N>| -> Vec<_> {
+        mov.after
+            .iter()
+            .map(|node| node.as_ref().map(Node::texture_name))
+            .collect()
+    };
+    a.coords == b.coords
+        && a.before
+            .iter()
+            .map(Node::texture_name)
+            .eq(b.before.iter().map(Node::texture_name))
+        && after(a) == after(b)
+}
+
+/// Whether the boards have the same hexes, nodes and rules, without needing `N: PartialEq`.
+pub(crate) fn same_board<N: Node>(a: &Board<N>, b: &Board<N>) -> bool {
+    let nodes = |board: &Board<N>| -> Vec<_> {
+        board
+            .nodes_iter()
+            .map(|(_, node)| node.map(Node::texture_name))
+            .collect()
+    };
+    // Masks keep their hexes in order, so the nodes line up if they match
+    a.mask() == b.mask()
+        && nodes(a) == nodes(b)
+        && N::write_rules(a.rules()) == N::write_rules(b.rules())
+}
+
+/// Save the game to a file, overwriting whatever was there.
+pub fn save_to_file<N: Node + Clone>(game: &Game<N>, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, write(game))
+}
+
+/// Load a game from a save file.
+pub fn load_from_file<N: Node + Clone>(path: impl AsRef<Path>) -> Result<Game<N>, LoadError> {
+    read(&fs::read_to_string(path)?)
+}
+
+/// Write a move as its `x y before after` groups, each with a space in front.
+pub(crate) fn write_move<N: Node>(mov: &Move<N>) -> String {
+    let mut out = String::new();
+    for ((coord, before), after) in mov.coords.iter().zip(&mov.before).zip(&mov.after) {
+        out.push_str(&format!(
+            " {} {} {} {}",
+            coord.x,
+            coord.y,
+            before.texture_name(),
+            after.as_ref().map_or("-", Node::texture_name)
+        ));
+    }
+    out
+}
+
+/// Read a move back out of its `x y before after` groups.
+/// Returns `None` if the groups are garbage, and `Some(Err)` if a node is unknown.
+/// The coordinates aren't checked against any board.
+pub(crate) fn parse_move<N: Node>(groups: &[&str]) -> Option<Result<Move<N>, LoadError>> {
+    let groups = groups.chunks_exact(4);
+    if groups.len() == 0 || !groups.remainder().is_empty() {
+        return None;
+    }
+    let mut mov = Move {
+        coords: Vec::new(),
+        before: Vec::new(),
+        after: Vec::new(),
+    };
+    for group in groups {
+        mov.coords.push(Coordinate::new(
+            group[0].parse().ok()?,
+            group[1].parse().ok()?,
+        ));
+        let after = match group[3] {
+            "-" => Ok(None),
+            name => parse_node(name).map(Some),
+        };
+        match (parse_node(group[2]), after) {
+            (Ok(before), Ok(after)) => {
+                mov.before.push(before);
+                mov.after.push(after);
+            }
+            (Err(err), _) | (_, Err(err)) => return Some(Err(err)),
+        }
+    }
+    Some(Ok(mov))
+}
+
+/// Returns `None` if the numbers are garbage, and `Some(Err)` if they're off the board.
+fn parse_coord<N: Node>(
+    board: &Board<N>,
+    x: &str,
+    y: &str,
+) -> Option<Result<Coordinate, LoadError>> {
+    let coord = Coordinate::new(x.parse().ok()?, y.parse().ok()?);
+    Some(if board.in_bounds(coord) {
+        Ok(coord)
+    } else {
+        Err(LoadError::OutOfBounds(coord))
+    })
+}
+
+fn parse_node<N: Node>(name: &str) -> Result<N, LoadError> {
+    N::from_texture_name(name).ok_or_else(|| LoadError::UnknownNode(name.to_owned()))
+}
@@ -0,0 +1,35 @@
+//! Points for combos, and how long and how many moves a game took.
+
+use crate::{Game, Move, Node};
+
+use std::time::Duration;
+
+/// How many points each node cleared off the board is worth, unless the game says otherwise.
+pub const POINTS_PER_NODE: u32 = 10;
+
+/// How well a game is going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    /// Points for every combo that hasn't been undone.
+    pub points: u32,
+    /// How many combos were made, counting ones that were undone later.
+    pub moves: u32,
+    /// How long the game has been played for.
+    pub elapsed: Duration,
+}
+
+impl Score {
+    /// Add up the points for the game so far.
+    pub fn of<N: Node + Clone>(game: &Game<N>) -> Self {
+        Self {
+            points: game.history().iter().map(N::points).sum(),
+            moves: game.moves(),
+            elapsed: game.elapsed(),
+        }
+    }
+}
+
+/// The default `Node::points`: everything that goes away is worth `POINTS_PER_NODE`.
+pub fn cleared_points<N>(mov: &Move<N>) -> u32 {
+    mov.after.iter().filter(|after| after.is_none()).count() as u32 * POINTS_PER_NODE
+}
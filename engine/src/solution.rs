@@ -0,0 +1,104 @@
+//! Solutions spelled out combo by combo, so they can be shared or shown.
+//!
+//! The solver finds a line as a list of coordinates to click;
+//! `solution::steps` turns that into the moves it makes on a board,
+//! with what each combo takes off the board and what it leaves behind.
+//!
+//! A solution file looks like this:
+//!
+//! ```text
+//! mofang-solution 1
+//! game mofang
+//! combo 3 0 fire - 4 0 metal -
+//! combo -1 2 yin creation 2 1 yang creation
+//! ```
+//!
+//! Each `combo` line is a list of `x y before after` groups, like moves in a save.
+
+use crate::{
+    save::{self, LoadError},
+    Board, Coordinate, Game, Move, Node,
+};
+
+/// The newest solution version we know how to write.
+pub const SOLUTION_VERSION: u32 = 1;
+
+const HEADER: &str = "mofang-solution";
+
+/// Play the line out on the board, and return the moves it makes.
+///
+/// If one of the combos can't be made, returns its index instead.
+pub fn steps<N: Node + Clone>(
+    board: &Board<N>,
+    line: &[Vec<Coordinate>],
+) -> Result<Vec<Move<N>>, usize> {
+    let mut game = Game::new(board.clone());
+    for (idx, combo) in line.iter().enumerate() {
+        for &coord in combo {
+            game.click(coord);
+        }
+        // Anything else would mean some of the clicks didn't go how the line said
+        let made = game.history().get(idx).map(|mov| &mov.coords);
+        if game.history().len() != idx + 1 || made != Some(combo) {
+            return Err(idx);
+        }
+    }
+    Ok(game.history().to_vec())
+}
+
+/// One line about a step, like `yin (-1, 2) + yang (2, 1) -> creation (-1, 2), creation (2, 1)`.
+pub fn describe<N: Node>(mov: &Move<N>) -> String {
+    let consumed: Vec<_> = mov
+        .coords
+        .iter()
+        .zip(&mov.before)
+        .map(|(coord, node)| format!("{} ({}, {})", node.texture_name(), coord.x, coord.y))
+        .collect();
+    let produced: Vec<_> = mov
+        .coords
+        .iter()
+        .zip(&mov.after)
+        .filter_map(|(coord, node)| {
+            node.as_ref()
+                .map(|node| format!("{} ({}, {})", node.texture_name(), coord.x, coord.y))
+        })
+        .collect();
+    if produced.is_empty() {
+        consumed.join(" + ")
+    } else {
+        format!("{} -> {}", consumed.join(" + "), produced.join(", "))
+    }
+}
+
+/// Write the steps out in the format described in the module docs.
+pub fn write<N: Node>(steps: &[Move<N>]) -> String {
+    let mut out = format!("{} {}\ngame {}\n", HEADER, SOLUTION_VERSION, N::name());
+    for mov in steps {
+        out.push_str(&format!("combo{}\n", save::write_move(mov)));
+    }
+    out
+}
+
+/// Read steps back out of the format described in the module docs.
+pub fn read<N: Node>(text: &str) -> Result<Vec<Move<N>>, LoadError> {
+    let lines = save::lines(text, HEADER, SOLUTION_VERSION)?;
+
+    let mut game_named = false;
+    let mut steps = Vec::new();
+    for (line_no, line) in lines {
+        let malformed = || LoadError::Malformed {
+            line: line_no,
+            text: line.to_owned(),
+        };
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["game", name] if *name == N::name() => game_named = true,
+            ["game", name] => return Err(LoadError::WrongGame((*name).to_owned())),
+            ["combo", groups @ ..] => steps.push(save::parse_move(groups).ok_or_else(malformed)??),
+            _ => return Err(malformed()),
+        }
+    }
+    if !game_named {
+        return Err(LoadError::NotASave);
+    }
+    Ok(steps)
+}
@@ -0,0 +1,415 @@
+use crate::{difficulty::Analyzer, Board, Coordinate, GenError, GenOptions, Node, PartialResult};
+
+use std::{collections::HashSet, hash::Hash};
+
+/// Depth-first solver that plays a board purely through `Node::can_select` and `Node::select`,
+/// so it works for any game.
+pub struct Solver {
+    /// How many distinct board states to look at before giving up.
+    pub max_states: usize,
+    /// When generating a solvable game, how many leftover nodes we're allowed to
+    /// take off the board to turn an almost-solution into a real one.
+    pub max_repairs: usize,
+    /// When generating a solvable game, how many boards to try before giving up.
+    pub max_attempts: usize,
+}
+
+/// What the solver found out about a board.
+pub enum Solution {
+    /// The board can be cleared by selecting these combos in order.
+    /// Each combo is listed in the order its coordinates should be clicked.
+    Solved(Vec<Vec<Coordinate>>),
+    /// Every line was tried and none of them clear the board.
+    Impossible,
+    /// We ran out of budget before finding out either way.
+    GaveUp,
+}
+
+impl Solution {
+    pub fn is_solved(&self) -> bool {
+        matches!(self, Solution::Solved(_))
+    }
+}
+
+/// A combo that could be made right now, and what the solver thinks of the board after it.
+pub struct Hint {
+    /// In an order the coordinates can be clicked in.
+    pub combo: Vec<Coordinate>,
+    pub outlook: Solution,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            max_states: 5_000,
+            max_repairs: 4,
+            max_attempts: 64,
+        }
+    }
+}
+
+impl Solver {
+    /// Try to find a line that clears the board.
+    pub fn solve<N: Node + Clone + Hash>(&self, board: &Board<N>) -> Solution {
+        self.search(board).0
+    }
+
+    /// Every legal combo on the board, best first:
+    /// ones that keep the board solvable, then ones we couldn't make our mind up about,
+    /// then dead ends.
+    ///
+    /// This runs the solver once per combo, so it can be slow on a full board.
+    pub fn rank_combos<N: Node + Clone + Hash>(&self, board: &Board<N>) -> Vec<Hint> {
+        let mut hints: Vec<_> = legal_combos(board)
+            .into_iter()
+            .map(|combo| {
+                let mut after = board.clone();
+                apply_combo(&mut after, &combo);
+                Hint {
+                    outlook: self.solve(&after),
+                    combo,
+                }
+            })
+            .collect();
+        hints.sort_by_key(|hint| match hint.outlook {
+            Solution::Solved(_) => 0,
+            Solution::GaveUp => 1,
+            Solution::Impossible => 2,
+        });
+        hints
+    }
+
+    /// One combo worth making next, or `None` if there aren't any legal combos at all.
+    ///
+    /// If the solver can clear the board this is the first step of its line,
+    /// otherwise it's just whatever combo is legal.
+    /// Only runs the solver once, so it's much cheaper than `rank_combos`.
+    pub fn hint<N: Node + Clone + Hash>(&self, board: &Board<N>) -> Option<Vec<Coordinate>> {
+        match self.solve(board) {
+            Solution::Solved(mut line) if !line.is_empty() => Some(line.swap_remove(0)),
+            _ => legal_combos(board).into_iter().next(),
+        }
+    }
+
+    /// Generate a standard board that the solver has proven can be cleared.
+    /// See `solvable_game_with`.
+    pub fn solvable_game<N: Node + Clone + Hash>(
+        &self,
+        seed: u64,
+    ) -> Result<(Board<N>, Vec<Vec<Coordinate>>), GenError> {
+        self.solvable_game_with(seed, &N::gen_options())
+    }
+
+    /// Generate a board with `Node::new_game_with` that the solver has proven can be cleared.
+    ///
+    /// If the solver can't clear a board but gets within `max_repairs` nodes of it,
+    /// and none of those leftovers were ever touched, they're removed from the board
+    /// so that the line it found works.
+    /// Otherwise the board is thrown out and we reroll.
+    /// If the options ask for a tier, boards that `Analyzer::default` rates differently
+    /// are thrown out too.
+    ///
+    /// The seed itself is tried first; rerolls draw their seeds from an rng seeded with it,
+    /// so this is still deterministic.
+    /// Returns the board and the solution that was found for it.
+    /// Fails if the options can't lay out a board at all,
+    /// or if none of the first `max_attempts` boards worked out.
+    pub fn solvable_game_with<N: Node + Clone + Hash>(
+        &self,
+        seed: u64,
+        options: &GenOptions<N>,
+    ) -> Result<(Board<N>, Vec<Vec<Coordinate>>), GenError> {
+        self.solvable_attempt(seed, options)
+            .map(|(_, board, line)| (board, line))
+    }
+
+    /// `solvable_game_with`, but also returns the seed of the attempt that worked.
+    /// That seed works on the first try by itself, so it makes the same board again
+    /// without the tier, which is how `difficulty::seed_in_tier` finds seeds.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn solvable_attempt<N: Node + Clone + Hash>(
+        &self,
+        seed: u64,
+        options: &GenOptions<N>,
+    ) -> Result<(u64, Board<N>, Vec<Vec<Coordinate>>), GenError> {
+        let rand = fastrand::Rng::with_seed(seed);
+        let mut attempt = seed;
+        // Whether anything could be cleared, just not in the tier
+        let mut cleared_any = false;
+        for _ in 0..self.max_attempts {
+            if let Some((board, line)) = self.try_solvable(attempt, options)? {
+                match options.tier {
+                    Some(tier) if Analyzer::default().rate_solved(&board, &line).tier != tier => {
+                        cleared_any = true
+                    }
+                    _ => return Ok((attempt, board, line)),
+                }
+            }
+            attempt = rand.u64(..);
+        }
+        let attempts = self.max_attempts;
+        Err(match options.tier {
+            Some(tier) if cleared_any => GenError::NotInTier { tier, attempts },
+            _ => GenError::Unsolvable { attempts },
+        })
+    }
+
+    /// Lay out a board from this seed and solve it, repairing it if it's close.
+    /// Returns `None` if it's no good, and an error if it couldn't be laid out at all.
+    #[allow(clippy::type_complexity)]
+    fn try_solvable<N: Node + Clone + Hash>(
+        &self,
+        seed: u64,
+        options: &GenOptions<N>,
+    ) -> Result<Option<(Board<N>, Vec<Vec<Coordinate>>)>, GenError> {
+        let mut board = N::new_game_with(seed, options)?;
+        Ok(match self.search(&board) {
+            (Solution::Solved(solution), _) => Some((board, solution)),
+            (_, Some(closest)) if closest.leftovers.len() <= self.max_repairs => {
+                for &coord in &closest.leftovers {
+                    board
+                        .set_node(coord, None)
+                        .expect("leftovers are on the board");
+                }
+                if replays_to_empty(&board, &closest.line) {
+                    Some((board, closest.line))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+    }
+
+    fn search<N: Node + Clone + Hash>(&self, board: &Board<N>) -> (Solution, Option<Repairable>) {
+        let mut search = Search {
+            max_states: self.max_states,
+            board: board.clone(),
+            path: Vec::new(),
+            dead: HashSet::new(),
+            states: 0,
+            closest: None,
+        };
+        let solution = match search.step() {
+            Some(true) => Solution::Solved(search.path),
+            Some(false) => Solution::Impossible,
+            None => Solution::GaveUp,
+        };
+        (solution, search.closest)
+    }
+}
+
+/// A line that clears everything except some nodes that were never touched.
+struct Repairable {
+    line: Vec<Vec<Coordinate>>,
+    leftovers: Vec<Coordinate>,
+}
+
+struct Search<N: Node> {
+    max_states: usize,
+    board: Board<N>,
+    path: Vec<Vec<Coordinate>>,
+    /// Hashes of boards we know can't be cleared.
+    dead: HashSet<u64>,
+    states: usize,
+    closest: Option<Repairable>,
+}
+
+impl<N: Node + Clone + Hash> Search<N> {
+    /// Returns `Some(true)` if solved (leaving the solution in `path`),
+    /// `Some(false)` if this board is a dead end, and `None` if we ran out of budget.
+    fn step(&mut self) -> Option<bool> {
+        let leftovers: Vec<_> = self
+            .board
+            .nodes_iter()
+            .filter_map(|(coord, node)| node.map(|_| coord))
+            .collect();
+        if leftovers.is_empty() {
+            return Some(true);
+        }
+        self.note_leftovers(leftovers);
+
+        let key = board_hash(&self.board);
+        if self.dead.contains(&key) {
+            return Some(false);
+        }
+        self.states += 1;
+        if self.states > self.max_states {
+            return None;
+        }
+
+        for combo in legal_combos(&self.board) {
+            let previous = apply_combo(&mut self.board, &combo);
+            self.path.push(combo);
+            let result = self.step();
+            if result != Some(false) {
+                return result;
+            }
+            let combo = self.path.pop().unwrap();
+            for (coord, node) in combo.into_iter().zip(previous) {
+                self.board
+                    .set_node(coord, node)
+                    .expect("combos are on the board");
+            }
+        }
+
+        self.dead.insert(key);
+        Some(false)
+    }
+
+    /// Remember this line if it's the closest repairable one so far.
+    fn note_leftovers(&mut self, leftovers: Vec<Coordinate>) {
+        let closer = match &self.closest {
+            Some(closest) => leftovers.len() < closest.leftovers.len(),
+            None => true,
+        };
+        let untouched = || {
+            leftovers
+                .iter()
+                .all(|coord| !self.path.iter().flatten().any(|c| c == coord))
+        };
+        if closer && untouched() {
+            self.closest = Some(Repairable {
+                line: self.path.clone(),
+                leftovers,
+            });
+        }
+    }
+}
+
+/// Every complete combo that could be selected on this board right now.
+///
+/// Each combo is listed in an order its coordinates can legally be clicked in.
+/// Combos made of the same coordinates are only listed once.
+pub fn legal_combos<N: Node>(board: &Board<N>) -> Vec<Vec<Coordinate>> {
+    let mut out = Vec::new();
+    find_combos(board, |combo| {
+        out.push(combo.to_vec());
+        false
+    });
+    out
+}
+
+/// Whether there's any combo that could be selected on this board right now.
+///
+/// Stops at the first one, so it's much cheaper than `legal_combos` on a busy board.
+pub fn any_legal_combo<N: Node>(board: &Board<N>) -> bool {
+    find_combos(board, |_| true)
+}
+
+/// Call `found` with each legal combo until it returns `true`.
+/// Returns whether it did.
+fn find_combos<N: Node>(board: &Board<N>, mut found: impl FnMut(&[Coordinate]) -> bool) -> bool {
+    let occupied: Vec<_> = board
+        .nodes_iter()
+        .filter_map(|(coord, node)| node.map(|_| coord))
+        .collect();
+    let mut seen = HashSet::new();
+    extend_combo(board, &occupied, &mut Vec::new(), &mut seen, &mut found)
+}
+
+fn extend_combo<N: Node>(
+    board: &Board<N>,
+    occupied: &[Coordinate],
+    selected: &mut Vec<Coordinate>,
+    seen: &mut HashSet<Vec<(i32, i32)>>,
+    found: &mut impl FnMut(&[Coordinate]) -> bool,
+) -> bool {
+    for &coord in occupied {
+        if selected.contains(&coord) {
+            continue;
+        }
+        let node = match board.get_node(coord) {
+            Some(it) if it.can_select(board, &coord, selected) => it,
+            _ => continue,
+        };
+        let combo: Vec<_> = selected
+            .iter()
+            .flat_map(|c| board.get_node(*c))
+            .chain(Some(node))
+            .collect();
+        let result = N::select(&combo, board.rules());
+        if !result.is_valid() {
+            continue;
+        }
+
+        selected.push(coord);
+        // Picking the same nodes in a different order gets us to the same place
+        let mut key: Vec<_> = selected.iter().map(|c| (c.x, c.y)).collect();
+        key.sort_unstable();
+        let done = seen.insert(key)
+            && match result {
+                PartialResult::Success(_) => found(selected),
+                _ => extend_combo(board, occupied, selected, seen, found),
+            };
+        selected.pop();
+        if done {
+            return true;
+        }
+    }
+    false
+}
+
+/// Select the combo and write the results to the board.
+/// Returns what used to be in each slot.
+///
+/// Panics if the combo isn't a successful selection.
+pub(crate) fn apply_combo<N: Node + Clone>(
+    board: &mut Board<N>,
+    combo: &[Coordinate],
+) -> Vec<Option<N>> {
+    let previous: Vec<_> = combo.iter().map(|c| board.get_node(*c).cloned()).collect();
+    let nodes: Vec<_> = previous.iter().flatten().collect();
+    let change = match N::select(&nodes, board.rules()) {
+        PartialResult::Success(change) => change,
+        _ => panic!("tried to apply a combo that isn't a match"),
+    };
+    for (&slot, update) in combo.iter().zip(change) {
+        board
+            .set_node(slot, update)
+            .expect("combos are on the board");
+    }
+    previous
+}
+
+/// Check that clicking through the line, one coordinate at a time, is legal and clears the board.
+fn replays_to_empty<N: Node + Clone>(board: &Board<N>, line: &[Vec<Coordinate>]) -> bool {
+    let mut board = board.clone();
+    for combo in line {
+        for (idx, coord) in combo.iter().enumerate() {
+            let selectable = matches!(
+                board.get_node(*coord),
+                Some(n) if n.can_select(&board, coord, &combo[..idx])
+            );
+            if !selectable {
+                return false;
+            }
+        }
+        let nodes: Vec<_> = combo.iter().flat_map(|c| board.get_node(*c)).collect();
+        if !matches!(N::select(&nodes, board.rules()), PartialResult::Success(_)) {
+            return false;
+        }
+        apply_combo(&mut board, combo);
+    }
+    board.node_count() == 0
+}
+
+/// FNV-1a of where every node is and its `Node::index`.
+/// Playouts are seeded with this, so it can't change between machines or Rust versions
+/// the way `DefaultHasher` can, or boards would rate differently there.
+pub(crate) fn board_hash<N: Node>(board: &Board<N>) -> u64 {
+    board
+        .nodes_iter()
+        .filter_map(|(coord, node)| Some((coord, node?.index() as u32)))
+        .flat_map(|(coord, index)| {
+            [
+                coord.x.to_le_bytes(),
+                coord.y.to_le_bytes(),
+                index.to_le_bytes(),
+            ]
+            .concat()
+        })
+        .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
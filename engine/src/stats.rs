@@ -0,0 +1,276 @@
+//! Keeping track of how someone's been doing across lots of games.
+//!
+//! Stats are kept separately for each game and board size and shape,
+//! along with personal bests for each seed that's been won, and written out like this:
+//!
+//! ```text
+//! mofang-stats 3
+//! record mofang 11 hexagon 12 7 2 4 183500 1320
+//! record mofang 13 mask-5c0ffee5 1 0 0 0 - -
+//! record sigmar 11 hexagon 3 0 0 0 - -
+//! seed mofang 1234 201000 44 1320
+//! ```
+//!
+//! Each `record` line is the game, diameter, shape (from `Mask::shape_name`), games played,
+//! games won, current win streak, best win streak, best time in milliseconds and best score,
+//! with `-` if there hasn't been a win yet.
+//! Records from before version 3 don't have a shape, and are read as hexagons.
+//! Each `seed` line is the game, seed, best time in milliseconds, fewest combos and best score.
+
+use crate::{
+    save::{self, LoadError},
+    Game, Mask, Node, Status,
+};
+
+use std::time::Duration;
+
+/// The newest stats version we know how to write.
+///
+/// Version 2 added per-seed bests, and version 3 added the shape to records.
+pub const STATS_VERSION: u32 = 3;
+
+const HEADER: &str = "mofang-stats";
+
+/// Every record there is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Sorted by game, then diameter, then shape.
+    records: Vec<Record>,
+    /// Sorted by game, then seed.
+    seeds: Vec<SeedBest>,
+}
+
+/// How it's gone for one game at one board size and shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub game: String,
+    pub diameter: i32,
+    /// `Mask::shape_name` of the board, so themed layouts don't share records with hexagons.
+    pub shape: String,
+    pub played: u32,
+    pub won: u32,
+    /// How many games in a row have been won, up to now.
+    pub streak: u32,
+    pub best_streak: u32,
+    /// The fastest win.
+    pub best_time: Option<Duration>,
+    /// The highest scoring win.
+    pub best_score: Option<u32>,
+}
+
+/// The best wins on one seed.
+/// Each of these is the best on its own, so they might come from different games.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedBest {
+    pub game: String,
+    pub seed: u64,
+    pub time: Duration,
+    /// The fewest combos it took.
+    pub moves: u32,
+    pub points: u32,
+}
+
+impl Record {
+    fn new(game: &str, diameter: i32, shape: String) -> Self {
+        Self {
+            game: game.to_owned(),
+            diameter,
+            shape,
+            played: 0,
+            won: 0,
+            streak: 0,
+            best_streak: 0,
+            best_time: None,
+            best_score: None,
+        }
+    }
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// The record for this game on boards like this, if it's ever been played.
+    pub fn get(&self, game: &str, mask: &Mask) -> Option<&Record> {
+        let shape = mask.shape_name();
+        self.records
+            .iter()
+            .find(|r| r.game == game && r.diameter == mask.diameter() && r.shape == shape)
+    }
+
+    pub fn seed_bests(&self) -> &[SeedBest] {
+        &self.seeds
+    }
+
+    /// The bests on this seed, if it's ever been won.
+    pub fn best_on_seed(&self, game: &str, seed: u64) -> Option<&SeedBest> {
+        self.seeds.iter().find(|s| s.game == game && s.seed == seed)
+    }
+
+    /// Count a game that's done with.
+    ///
+    /// Won games count as wins; anything else is a loss and breaks the streak,
+    /// so call this when a game is won or given up on, not when it's stuck,
+    /// since a stuck game can still be undone.
+    /// Assisted wins still count as wins, but not towards any bests.
+    pub fn record<N: Node + Clone>(&mut self, game: &Game<N>) {
+        let diameter = game.board().diameter();
+        let shape = game.board().mask().shape_name();
+        let pos = match self.records.binary_search_by(|r| {
+            (r.game.as_str(), r.diameter, r.shape.as_str()).cmp(&(
+                N::name(),
+                diameter,
+                shape.as_str(),
+            ))
+        }) {
+            Ok(pos) => pos,
+            Err(pos) => {
+                self.records
+                    .insert(pos, Record::new(N::name(), diameter, shape));
+                pos
+            }
+        };
+        let record = &mut self.records[pos];
+
+        record.played += 1;
+        if game.status() == Status::Won {
+            record.won += 1;
+            record.streak += 1;
+            record.best_streak = record.best_streak.max(record.streak);
+            if game.assisted() {
+                return;
+            }
+
+            let score = game.score();
+            record.best_time = Some(
+                record
+                    .best_time
+                    .map_or(score.elapsed, |t| t.min(score.elapsed)),
+            );
+            record.best_score = Some(
+                record
+                    .best_score
+                    .map_or(score.points, |s| s.max(score.points)),
+            );
+
+            if let Some(seed) = game.seed() {
+                match self
+                    .seeds
+                    .binary_search_by(|s| (s.game.as_str(), s.seed).cmp(&(N::name(), seed)))
+                {
+                    Ok(pos) => {
+                        let best = &mut self.seeds[pos];
+                        best.time = best.time.min(score.elapsed);
+                        best.moves = best.moves.min(score.moves);
+                        best.points = best.points.max(score.points);
+                    }
+                    Err(pos) => self.seeds.insert(
+                        pos,
+                        SeedBest {
+                            game: N::name().to_owned(),
+                            seed,
+                            time: score.elapsed,
+                            moves: score.moves,
+                            points: score.points,
+                        },
+                    ),
+                }
+            }
+        } else {
+            record.streak = 0;
+        }
+    }
+
+    /// Write the stats out in the format described in the module docs.
+    pub fn write(&self) -> String {
+        let mut out = format!("{} {}\n", HEADER, STATS_VERSION);
+        for r in &self.records {
+            out.push_str(&format!(
+                "record {} {} {} {} {} {} {} {} {}\n",
+                r.game,
+                r.diameter,
+                r.shape,
+                r.played,
+                r.won,
+                r.streak,
+                r.best_streak,
+                r.best_time
+                    .map_or("-".to_owned(), |t| t.as_millis().to_string()),
+                r.best_score.map_or("-".to_owned(), |s| s.to_string()),
+            ));
+        }
+        for s in &self.seeds {
+            out.push_str(&format!(
+                "seed {} {} {} {} {}\n",
+                s.game,
+                s.seed,
+                s.time.as_millis(),
+                s.moves,
+                s.points
+            ));
+        }
+        out
+    }
+
+    /// Read stats back out of the format described in the module docs.
+    pub fn read(text: &str) -> Result<Self, LoadError> {
+        let lines = save::lines(text, HEADER, STATS_VERSION)?;
+
+        let mut out = Self::new();
+        for (line_no, line) in lines {
+            let malformed = || LoadError::Malformed {
+                line: line_no,
+                text: line.to_owned(),
+            };
+            let mut words: Vec<_> = line.split_whitespace().collect();
+            // Older records don't say, but almost everything was a hexagon back then
+            if words.first() == Some(&"record") && words.len() == 9 {
+                words.insert(3, "hexagon");
+            }
+            let num = |word: &str| word.parse::<u32>().map_err(|_| malformed());
+            match words.as_slice() {
+                ["record", game, diameter, shape, played, won, streak, best_streak, best_time, best_score] =>
+                {
+                    let best_time = match *best_time {
+                        "-" => None,
+                        ms => Some(Duration::from_millis(ms.parse().map_err(|_| malformed())?)),
+                    };
+                    out.records.push(Record {
+                        game: (*game).to_owned(),
+                        diameter: diameter.parse().map_err(|_| malformed())?,
+                        shape: (*shape).to_owned(),
+                        played: num(played)?,
+                        won: num(won)?,
+                        streak: num(streak)?,
+                        best_streak: num(best_streak)?,
+                        best_time,
+                        best_score: match *best_score {
+                            "-" => None,
+                            score => Some(num(score)?),
+                        },
+                    });
+                }
+                ["seed", game, seed, time, moves, points] => {
+                    out.seeds.push(SeedBest {
+                        game: (*game).to_owned(),
+                        seed: seed.parse().map_err(|_| malformed())?,
+                        time: Duration::from_millis(time.parse().map_err(|_| malformed())?),
+                        moves: num(moves)?,
+                        points: num(points)?,
+                    });
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        out.records
+            .sort_by(|a, b| (&a.game, a.diameter, &a.shape).cmp(&(&b.game, b.diameter, &b.shape)));
+        out.seeds
+            .sort_by(|a, b| (&a.game, a.seed).cmp(&(&b.game, b.seed)));
+        Ok(out)
+    }
+}
@@ -0,0 +1,524 @@
+//! Generating boards by playing the game backwards.
+//!
+//! We start with an empty board and keep "un-matching" combos onto it,
+//! checking each time that the combo could really be selected there going forwards.
+//! Read backwards, the combos are a line that clears the board, so every board made this way
+//! comes with a solution.
+
+use crate::{
+    difficulty::{Analyzer, Tier},
+    Board, GenError, GenOptions, Node, PartialResult,
+};
+
+use hex2d::Coordinate;
+use itertools::Itertools;
+
+use std::{collections::HashSet, error::Error, fmt, hash::Hash};
+
+/// How many times `generate` tries to lay out a plan before giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+/// A combo the game allows, in an order it can be clicked in.
+#[derive(Debug, Clone)]
+struct Pattern<N> {
+    before: Vec<N>,
+    after: Vec<Option<N>>,
+}
+
+/// Why `generate` couldn't make a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmatchError {
+    /// The nodes in the options can't all be matched away, like with an odd element out.
+    Unpairable,
+    /// The nodes can be matched away, but we never found room on the board to un-match them.
+    OutOfAttempts,
+    /// The options can't make a board at all.
+    Options(GenError),
+    /// Boards got made, but none of them were in the tier the options asked for.
+    NotInTier(Tier),
+}
+
+impl From<GenError> for UnmatchError {
+    fn from(err: GenError) -> Self {
+        UnmatchError::Options(err)
+    }
+}
+
+impl fmt::Display for UnmatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmatchError::Unpairable => write!(f, "the nodes can't all be matched away"),
+            UnmatchError::OutOfAttempts => write!(
+                f,
+                "couldn't fit the combos on the board in {} attempts",
+                MAX_ATTEMPTS
+            ),
+            UnmatchError::Options(err) => err.fmt(f),
+            UnmatchError::NotInTier(tier) => write!(
+                f,
+                "none of the boards in {} attempts were {}",
+                MAX_ATTEMPTS, tier
+            ),
+        }
+    }
+}
+
+impl Error for UnmatchError {}
+
+/// Generate a board with exactly the nodes in the options, and the line that clears it.
+///
+/// The ring fill chances are ignored; nodes are placed in shells growing out from the center,
+/// skipping any holes in the mask.
+/// This is deterministic for a given seed.
+///
+/// Fails straight away if the options don't pass `GenOptions::validate`
+/// or the nodes can't be matched away at all,
+/// or after `MAX_ATTEMPTS` tries if they can but never fit on the board,
+/// or never make a board in the options' tier if they have one.
+pub fn generate<N>(
+    seed: u64,
+    options: &GenOptions<N>,
+) -> Result<(Board<N>, Vec<Vec<Coordinate>>), UnmatchError>
+where
+    N: Node + Clone + Hash + PartialEq,
+{
+    options.validate()?;
+
+    let mut bank = options.bank_nodes();
+    bank.extend(options.center.iter().cloned());
+    bank.extend(options.around_center.iter().cloned());
+    let patterns = patterns(&bank, &options.rules);
+    if unpairable(&bank, &patterns) {
+        return Err(UnmatchError::Unpairable);
+    }
+
+    let rand = fastrand::Rng::with_seed(seed);
+    // Whether any boards fit, just not in the tier
+    let mut made_any = false;
+    for _ in 0..MAX_ATTEMPTS {
+        // Retries keep drawing from the same rng so the seed still decides the board
+        let result = plan(&rand, &bank, &patterns, options.center.as_ref())
+            .and_then(|plan| place(&rand, options, plan));
+        if let Some((board, line)) = result {
+            match options.tier {
+                Some(tier) if Analyzer::default().rate_solved(&board, &line).tier != tier => {
+                    made_any = true
+                }
+                _ => return Ok((board, line)),
+            }
+        }
+    }
+    match options.tier {
+        Some(tier) if made_any => Err(UnmatchError::NotInTier(tier)),
+        _ => Err(UnmatchError::OutOfAttempts),
+    }
+}
+
+/// Can we prove there's no way to match away every node in the bank?
+///
+/// This searches much longer than `plan` does, but if it still runs out
+/// we say it might be pairable and let `generate` run out of attempts instead.
+fn unpairable<N>(bank: &[N], patterns: &[Pattern<N>]) -> bool
+where
+    N: Node + Clone + PartialEq,
+{
+    // Its own rng, so checking doesn't change which board the seed makes
+    let rand = fastrand::Rng::with_seed(0);
+    let mut search = Search::new(&rand, patterns, 1_000_000);
+    !search.run(&mut Multiset::from(bank), &mut Vec::new()) && search.budget > 0
+}
+
+/// Find every combo that can be made out of these nodes, and out of whatever those combos make.
+fn patterns<N>(bank: &[N], rules: &N::Rules) -> Vec<Pattern<N>>
+where
+    N: Node + Clone + PartialEq,
+{
+    let mut kinds: Vec<N> = Vec::new();
+    for node in bank {
+        if !kinds.contains(node) {
+            kinds.push(node.clone());
+        }
+    }
+
+    let mut out: Vec<Pattern<N>> = Vec::new();
+    let mut searched = 0;
+    // Keep going until the combos stop making new kinds of node
+    while searched < kinds.len() {
+        searched = kinds.len();
+        out.clear();
+        extend_pattern(&kinds, rules, &mut Vec::new(), &mut out);
+        for pattern in &out {
+            for node in pattern.after.iter().flatten() {
+                if !kinds.contains(node) {
+                    kinds.push(node.clone());
+                }
+            }
+        }
+    }
+    out
+}
+
+fn extend_pattern<N>(kinds: &[N], rules: &N::Rules, before: &mut Vec<N>, out: &mut Vec<Pattern<N>>)
+where
+    N: Node + Clone + PartialEq,
+{
+    // Nothing in any game is this big, and it stops runaway Continues
+    const MAX_LEN: usize = 8;
+    if before.len() >= MAX_LEN {
+        return;
+    }
+    for kind in kinds {
+        before.push(kind.clone());
+        let refs: Vec<_> = before.iter().collect();
+        match N::select(&refs, rules) {
+            PartialResult::Success(after) => {
+                // The same nodes in a different order are the same combo
+                let duplicate = out.iter().any(|p| {
+                    p.before.len() == before.len() && Multiset::from(&p.before).contains(before)
+                });
+                if !duplicate {
+                    out.push(Pattern {
+                        before: before.clone(),
+                        after,
+                    });
+                }
+            }
+            PartialResult::Continue => extend_pattern(kinds, rules, before, out),
+            PartialResult::Failure => {}
+        }
+        before.pop();
+    }
+}
+
+/// Decide which combos to make, ignoring where anything is.
+///
+/// Whatever combo uses the center node is saved for last,
+/// so it's the first thing placed and the center is still open.
+fn plan<N>(
+    rand: &fastrand::Rng,
+    bank: &[N],
+    patterns: &[Pattern<N>],
+    center: Option<&N>,
+) -> Option<Vec<Pattern<N>>>
+where
+    N: Node + Clone + PartialEq,
+{
+    let mut left = Multiset::from(bank);
+    let finale = center.and_then(|center| {
+        let options: Vec<_> = patterns
+            .iter()
+            .filter(|p| {
+                p.before.contains(center)
+                    && p.after.iter().all(Option::is_none)
+                    && left.contains(&p.before)
+            })
+            .collect();
+        if options.is_empty() {
+            None
+        } else {
+            Some(options[rand.usize(..options.len())].clone())
+        }
+    });
+    if let Some(finale) = &finale {
+        left.remove(&finale.before);
+    }
+
+    let mut plan = Vec::new();
+    let mut search = Search::new(rand, patterns, 10_000);
+    if search.run(&mut left, &mut plan) {
+        plan.extend(finale);
+        Some(plan)
+    } else {
+        None
+    }
+}
+
+/// Depth-first search for a way to match away a bag of nodes.
+///
+/// Picking combos at random dead-ends almost every time
+/// (like pairing off elements so one is left over), so we back up instead.
+struct Search<'a, N> {
+    rand: &'a fastrand::Rng,
+    patterns: &'a [Pattern<N>],
+    kinds: Vec<N>,
+    /// Bags we already know can't be cleared.
+    dead: HashSet<Vec<usize>>,
+    /// How many more bags we'll look at before giving up.
+    budget: usize,
+}
+
+impl<'a, N> Search<'a, N>
+where
+    N: Node + Clone + PartialEq,
+{
+    fn new(rand: &'a fastrand::Rng, patterns: &'a [Pattern<N>], budget: usize) -> Self {
+        // Every kind of node that can show up, so bags can be compared by their counts
+        let mut kinds: Vec<N> = Vec::new();
+        for node in patterns.iter().flat_map(|p| p.before.iter()) {
+            if !kinds.contains(node) {
+                kinds.push(node.clone());
+            }
+        }
+        Self {
+            rand,
+            patterns,
+            kinds,
+            dead: HashSet::new(),
+            budget,
+        }
+    }
+
+    fn run(&mut self, left: &mut Multiset<N>, plan: &mut Vec<Pattern<N>>) -> bool {
+        if left.is_empty() {
+            return true;
+        }
+        let key = self.kinds.iter().map(|k| left.count(k)).collect_vec();
+        if self.budget == 0 || self.dead.contains(&key) {
+            return false;
+        }
+        self.budget -= 1;
+
+        let (board, coords) = spread_out(left);
+        let mut options: Vec<_> = self
+            .patterns
+            .iter()
+            .filter(|p| {
+                left.contains(&p.before) && {
+                    // Check the game would let us select it at all, like with metals in order
+                    let mut taken: Vec<Coordinate> = Vec::new();
+                    for node in &p.before {
+                        let (coord, _) = coords
+                            .iter()
+                            .find(|(c, n)| n == node && !taken.contains(c))
+                            .unwrap();
+                        taken.push(*coord);
+                    }
+                    clicks_through(&board, &taken, &p.after)
+                }
+            })
+            .collect();
+        // Try using up whatever there's the most of first, which keeps the counts even
+        // and dodges most dead ends (like an odd element out) before we have to back up
+        self.rand.shuffle(&mut options);
+        options.sort_by_key(|p| {
+            let scarcest = p.before.iter().map(|n| left.count(n)).min();
+            // and then whatever clears the most
+            let cleared = p.after.iter().filter(|n| n.is_none()).count();
+            std::cmp::Reverse((scarcest, cleared))
+        });
+
+        for pattern in options {
+            left.remove(&pattern.before);
+            left.add(pattern.after.iter().flatten());
+            plan.push(pattern.clone());
+            if self.run(left, plan) {
+                return true;
+            }
+            plan.pop();
+            left.remove(&pattern.after.iter().flatten().cloned().collect_vec());
+            left.add(&pattern.before);
+        }
+
+        // Running out of budget doesn't mean it's actually dead
+        if self.budget > 0 {
+            self.dead.insert(key);
+        }
+        false
+    }
+}
+
+/// Un-match the plan onto an empty board, last combo first.
+fn place<N>(
+    rand: &fastrand::Rng,
+    options: &GenOptions<N>,
+    plan: Vec<Pattern<N>>,
+) -> Option<(Board<N>, Vec<Vec<Coordinate>>)>
+where
+    N: Node + Clone + PartialEq,
+{
+    // How many spots to try for each combo before giving up on the plan
+    const TRIES: usize = 50;
+
+    let origin = Coordinate::new(0, 0);
+    let mut board = options.empty_board();
+    let mut center_open = options.center.is_some() && board.in_bounds(origin);
+    let mut line = Vec::new();
+
+    for pattern in plan.into_iter().rev() {
+        let placed = (0..TRIES).find_map(|attempt| {
+            // Fill in from the middle so everything has open space outside it,
+            // reaching further out if it's too crowded to select anything
+            let slack = (attempt / 10) as i32;
+            let mut coords: Vec<Coordinate> = Vec::new();
+            let mut takes_center = false;
+            for (before, after) in pattern.before.iter().zip(&pattern.after) {
+                let spots: Vec<_> = match after {
+                    // Whatever this made has to already be there
+                    Some(made) => board
+                        .nodes_iter()
+                        .filter(|(c, node)| *node == Some(made) && !coords.contains(c))
+                        .map(|(c, _)| c)
+                        .collect(),
+                    None if center_open
+                        && !takes_center
+                        && Some(before) == options.center.as_ref() =>
+                    {
+                        takes_center = true;
+                        vec![origin]
+                    }
+                    None => {
+                        let empty = board
+                            .nodes_iter()
+                            .filter(|&(c, node)| {
+                                node.is_none()
+                                    && !(center_open && c == origin)
+                                    && !coords.contains(&c)
+                            })
+                            .map(|(c, _)| c)
+                            .collect_vec();
+                        let innermost = empty.iter().map(|c| c.distance(origin)).min();
+                        empty
+                            .into_iter()
+                            .filter(|c| Some(c.distance(origin)) <= innermost.map(|d| d + slack))
+                            .collect()
+                    }
+                };
+                if spots.is_empty() {
+                    return None;
+                }
+                coords.push(spots[rand.usize(..spots.len())]);
+            }
+
+            let previous: Vec<_> = coords
+                .iter()
+                .zip(&pattern.before)
+                .map(|(&c, node)| board.set_node(c, Some(node.clone())))
+                .collect::<Result<_, _>>()
+                .expect("spots are on the board");
+            if clicks_through(&board, &coords, &pattern.after) {
+                Some((coords, takes_center))
+            } else {
+                for (&c, node) in coords.iter().zip(previous) {
+                    board.set_node(c, node).expect("spots are on the board");
+                }
+                None
+            }
+        });
+
+        let (coords, took_center) = placed?;
+        center_open &= !took_center;
+        line.push(coords);
+    }
+
+    line.reverse();
+    Some((board, line))
+}
+
+/// Could a player click these in order, and would it leave behind what we expect?
+fn clicks_through<N>(board: &Board<N>, coords: &[Coordinate], expected: &[Option<N>]) -> bool
+where
+    N: Node + PartialEq,
+{
+    let mut nodes = Vec::new();
+    for (idx, coord) in coords.iter().enumerate() {
+        let node = match board.get_node(*coord) {
+            Some(it) if it.can_select(board, coord, &coords[..idx]) => it,
+            _ => return false,
+        };
+        nodes.push(node);
+        match N::select(&nodes, board.rules()) {
+            PartialResult::Success(after) => return idx + 1 == coords.len() && after == expected,
+            PartialResult::Continue => {}
+            PartialResult::Failure => return false,
+        }
+    }
+    false
+}
+
+/// Put all the nodes in the bag on a board with a gap around each,
+/// so only the rules get in the way of selecting them.
+fn spread_out<N>(bag: &Multiset<N>) -> (Board<N>, Vec<(Coordinate, N)>)
+where
+    N: Node + Clone + PartialEq,
+{
+    let nodes = bag.nodes();
+    // Hexes with both coordinates even never touch each other
+    let spaced = |radius| {
+        Coordinate::new(0, 0)
+            .range_iter(radius)
+            .filter(|c: &Coordinate| c.x % 2 == 0 && c.y % 2 == 0)
+    };
+    let radius = (1..)
+        .find(|&radius| spaced(radius).count() >= nodes.len())
+        .unwrap();
+
+    let coords = spaced(radius).zip(nodes).collect_vec();
+    let board = Board::builder(radius * 2 + 1)
+        .nodes(coords.iter().cloned())
+        .build()
+        .expect("spaced hexes are on the board");
+    (board, coords)
+}
+
+/// A bag of nodes. Just a list, since there's only ever a handful of kinds.
+struct Multiset<N>(Vec<(N, usize)>);
+
+impl<N: Clone + PartialEq> Multiset<N> {
+    fn from<'a, I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = &'a N>,
+        N: 'a,
+    {
+        let mut this = Self(Vec::new());
+        this.add(nodes);
+        this
+    }
+
+    fn add<'a, I>(&mut self, nodes: I)
+    where
+        I: IntoIterator<Item = &'a N>,
+        N: 'a,
+    {
+        for node in nodes {
+            match self.0.iter_mut().find(|(n, _)| n == node) {
+                Some((_, count)) => *count += 1,
+                None => self.0.push((node.clone(), 1)),
+            }
+        }
+    }
+
+    fn count(&self, node: &N) -> usize {
+        self.0
+            .iter()
+            .find(|(n, _)| n == node)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Are all of these in the bag (counting duplicates)?
+    fn contains(&self, nodes: &[N]) -> bool {
+        nodes
+            .iter()
+            .all(|node| nodes.iter().filter(|n| *n == node).count() <= self.count(node))
+    }
+
+    fn nodes(&self) -> Vec<N> {
+        self.0
+            .iter()
+            .flat_map(|(node, count)| vec![node.clone(); *count])
+            .collect()
+    }
+
+    /// Take these out of the bag. They'd better be in it.
+    fn remove(&mut self, nodes: &[N]) {
+        for node in nodes {
+            if let Some((_, count)) = self.0.iter_mut().find(|(n, _)| n == node) {
+                *count -= 1;
+            }
+        }
+        self.0.retain(|(_, count)| *count > 0);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
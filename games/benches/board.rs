@@ -0,0 +1,153 @@
+//! How fast is the board at the things the solver does a zillion times?
+//!
+//! `Board` used to be a `HashMap`, so there's a copy of that here to compare against.
+//! Run with `cargo bench -p mofang_games --bench board`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hex2d::Coordinate;
+use mofang_engine::{solver::legal_combos, Board, Game, Node};
+use mofang_games::{MofangNode, SigmarNode};
+
+use std::collections::HashMap;
+
+/// The old board, just enough of it to count neighbors and iterate.
+struct HashBoard {
+    nodes: HashMap<Coordinate, Option<MofangNode>>,
+    radius: i32,
+}
+
+impl HashBoard {
+    fn from(board: &Board<MofangNode>) -> Self {
+        Self {
+            nodes: board
+                .nodes_iter()
+                .map(|(coord, node)| (coord, node.cloned()))
+                .collect(),
+            radius: board.radius(),
+        }
+    }
+
+    fn get_node(&self, coord: Coordinate) -> Option<&MofangNode> {
+        self.nodes.get(&coord).and_then(Option::as_ref)
+    }
+
+    fn nodes_iter(&self) -> impl Iterator<Item = (Coordinate, Option<&MofangNode>)> + '_ {
+        Coordinate::new(0, 0)
+            .range_iter(self.radius)
+            .map(move |c| (c, self.get_node(c)))
+    }
+
+    fn max_open_neighbors(&self, at: &Coordinate) -> usize {
+        match at
+            .neighbors()
+            .iter()
+            .position(|&coord| self.get_node(coord).is_some())
+        {
+            Some(pos) => {
+                at.neighbors()
+                    .iter()
+                    .cycle()
+                    .skip(pos + 1)
+                    .take(6)
+                    .fold((0, 0), |(maxrun, run), &neighbor| {
+                        if self.get_node(neighbor).is_some() {
+                            (maxrun.max(run), 0)
+                        } else {
+                            (maxrun, run + 1)
+                        }
+                    })
+                    .0
+            }
+            None => 6,
+        }
+    }
+}
+
+fn neighbors(c: &mut Criterion) {
+    let board = MofangNode::new_game(42).unwrap();
+    let old = HashBoard::from(&board);
+    let coords: Vec<_> = board.nodes_iter().map(|(coord, _)| coord).collect();
+
+    let mut group = c.benchmark_group("max_open_neighbors");
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                .map(|c| old.max_open_neighbors(black_box(c)))
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("array", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                .map(|c| board.max_open_neighbors(black_box(c)))
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+fn iteration(c: &mut Criterion) {
+    let board = MofangNode::new_game(42).unwrap();
+    let old = HashBoard::from(&board);
+
+    let mut group = c.benchmark_group("nodes_iter");
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            black_box(&old)
+                .nodes_iter()
+                .filter(|(_, n)| n.is_some())
+                .count()
+        })
+    });
+    group.bench_function("array", |b| {
+        b.iter(|| {
+            black_box(&board)
+                .nodes_iter()
+                .filter(|(_, n)| n.is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn cloning(c: &mut Criterion) {
+    let board = MofangNode::new_game(42).unwrap();
+    let old = HashBoard::from(&board);
+
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("hashmap", |b| b.iter(|| black_box(&old).nodes.clone()));
+    group.bench_function("array", |b| b.iter(|| black_box(&board).clone()));
+    group.finish();
+}
+
+/// What the controller does every frame.
+fn selectable(c: &mut Criterion) {
+    let mofang = Game::new(MofangNode::new_game(42).unwrap());
+    let sigmar = Game::new(SigmarNode::new_game(42).unwrap());
+    let coords: Vec<_> = mofang
+        .board()
+        .nodes_iter()
+        .map(|(coord, _)| coord)
+        .collect();
+
+    let mut group = c.benchmark_group("is_selectable");
+    group.bench_function("mofang", |b| {
+        b.iter(|| coords.iter().filter(|&&c| mofang.is_selectable(c)).count())
+    });
+    group.bench_function("sigmar", |b| {
+        b.iter(|| coords.iter().filter(|&&c| sigmar.is_selectable(c)).count())
+    });
+    group.finish();
+}
+
+fn combos(c: &mut Criterion) {
+    let board = MofangNode::new_game(42).unwrap();
+    c.bench_function("legal_combos", |b| {
+        b.iter(|| legal_combos(black_box(&board)))
+    });
+}
+
+criterion_group!(benches, neighbors, iteration, cloning, selectable, combos);
+criterion_main!(benches);
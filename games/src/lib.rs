@@ -1,4 +1,4 @@
 mod mofang;
 mod sigmar;
-pub use mofang::MofangNode;
-pub use sigmar::SigmarNode;
+pub use mofang::{CelestialMatches, MofangNode, MofangRules, DESTRUCTION_BONUS};
+pub use sigmar::{SigmarNode, QUINTESSENCE_BONUS};
@@ -1,10 +1,15 @@
 use enum_map::Enum;
-use hex2d::{Angle, Coordinate, Direction, Spin};
+use hex2d::{Coordinate, Direction};
 use itertools::Itertools;
-use mofang_engine::{all_unique, Board, Node, PartialResult};
+use mofang_engine::{
+    all_unique, generation, score, Board, GenError, GenOptions, Move, Node, PartialResult,
+};
+
+/// Matching all five elements with Destruction is worth this much on top of the usual points.
+pub const DESTRUCTION_BONUS: u32 = 100;
 
 /// One of the marbles on the game board.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Enum)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Enum)]
 pub enum MofangNode {
     Wood,
     Fire,
@@ -25,7 +30,49 @@ pub enum MofangNode {
     Qi,
 }
 
+/// Which variant of the rules to play Mofang's Garden with.
+///
+/// The default is the standard game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MofangRules {
+    /// Qi matches with any element, turning it into Qi.
+    pub qi_matches_elements: bool,
+    /// What Heavenly + Yang and Earthly + Yin do.
+    pub celestial_matches: CelestialMatches,
+    /// How many contiguous open neighbors most nodes need to be selectable.
+    pub freeness: usize,
+    /// How many contiguous open neighbors Qi needs to be selectable.
+    pub qi_freeness: usize,
+    /// How many contiguous open neighbors an element needs to be selected with a Human.
+    pub human_freeness: usize,
+}
+
+/// What Heavenly + Yang and Earthly + Yin do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CelestialMatches {
+    /// They don't match.
+    Off,
+    /// Both sides go away.
+    Cancel,
+    /// The Heavenly or Earthly goes away, leaving the Yang or Yin behind.
+    KeepYinYang,
+}
+
+impl Default for MofangRules {
+    fn default() -> Self {
+        Self {
+            qi_matches_elements: false,
+            celestial_matches: CelestialMatches::Cancel,
+            freeness: 3,
+            qi_freeness: 5,
+            human_freeness: 2,
+        }
+    }
+}
+
 impl Node for MofangNode {
+    type Rules = MofangRules;
+
     fn name() -> &'static str {
         "mofang"
     }
@@ -46,32 +93,92 @@ impl Node for MofangNode {
             MofangNode::Qi => "qi",
         }
     }
+    fn index(&self) -> usize {
+        self.clone() as usize
+    }
+    fn from_texture_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "wood" => MofangNode::Wood,
+            "fire" => MofangNode::Fire,
+            "earth" => MofangNode::Earth,
+            "metal" => MofangNode::Metal,
+            "water" => MofangNode::Water,
+            "heavenly" => MofangNode::Heavenly,
+            "earthly" => MofangNode::Earthly,
+            "human" => MofangNode::Human,
+            "yin" => MofangNode::Yin,
+            "yang" => MofangNode::Yang,
+            "creation" => MofangNode::Creation,
+            "destruction" => MofangNode::Destruction,
+            "qi" => MofangNode::Qi,
+            _ => return None,
+        })
+    }
+    fn write_rules(rules: &MofangRules) -> Vec<String> {
+        let celestial = match rules.celestial_matches {
+            CelestialMatches::Off => "off",
+            CelestialMatches::Cancel => "cancel",
+            CelestialMatches::KeepYinYang => "keep_yin_yang",
+        };
+        vec![
+            format!("qi_matches_elements={}", rules.qi_matches_elements),
+            format!("celestial_matches={}", celestial),
+            format!("freeness={}", rules.freeness),
+            format!("qi_freeness={}", rules.qi_freeness),
+            format!("human_freeness={}", rules.human_freeness),
+        ]
+    }
+    fn read_rules(words: &[&str]) -> Option<MofangRules> {
+        let mut rules = MofangRules::default();
+        for word in words {
+            let mut parts = word.splitn(2, '=');
+            match (parts.next()?, parts.next()?) {
+                ("qi_matches_elements", value) => rules.qi_matches_elements = value.parse().ok()?,
+                ("celestial_matches", "off") => rules.celestial_matches = CelestialMatches::Off,
+                ("celestial_matches", "cancel") => {
+                    rules.celestial_matches = CelestialMatches::Cancel
+                }
+                ("celestial_matches", "keep_yin_yang") => {
+                    rules.celestial_matches = CelestialMatches::KeepYinYang
+                }
+                ("freeness", value) => rules.freeness = value.parse().ok()?,
+                ("qi_freeness", value) => rules.qi_freeness = value.parse().ok()?,
+                ("human_freeness", value) => rules.human_freeness = value.parse().ok()?,
+                _ => return None,
+            }
+        }
+        Some(rules)
+    }
     fn can_select(
         &self,
         board: &Board<MofangNode>,
         coord: &Coordinate,
         selected: &[Coordinate],
     ) -> bool {
+        let rules = board.rules();
         let freeness_req = match selected {
             // Human magic
             [human_coord]
                 if matches!(board.get_node(*human_coord), Some(MofangNode::Human))
                     && self.is_elemental() =>
             {
-                2
+                rules.human_freeness
             }
-            _ => self.freeness_req(),
+            _ => self.freeness_req(rules),
         };
         board.max_open_neighbors(coord) >= freeness_req
     }
 
     /// Given a list of Nodes, see whether this pattern could exist
     /// and, if so, what to replace each Node with.
-    fn select(nodes: &[&MofangNode]) -> PartialResult<Vec<Option<MofangNode>>> {
+    fn select(
+        nodes: &[&MofangNode],
+        rules: &MofangRules,
+    ) -> PartialResult<Vec<Option<MofangNode>>> {
         match nodes.len() {
             0 => unreachable!("You can't select 0 nodes!"),
             1 => PartialResult::Continue,
-            2 if nodes[0].cancels_with(&nodes[1]) => PartialResult::Success(vec![None, None]),
+            2 if nodes[0].cancels_with(nodes[1]) => PartialResult::Success(vec![None, None]),
             _ => {
                 // TODO: I am bad at rust, not sure how sorted_unstable_by_key works
                 let (original_idxes, sorted): (Vec<_>, Vec<_>) = nodes
@@ -118,20 +225,20 @@ impl Node for MofangNode {
                     }
 
                     // Qi matches with elements
-                    /*
-                    [element, MofangNode::Qi] if element.is_elemental() => {
+                    [element, MofangNode::Qi]
+                        if rules.qi_matches_elements && element.is_elemental() =>
+                    {
                         PartialResult::Success(unsort(vec![Some(MofangNode::Qi), None]))
                     }
-                    */
 
-                    /*
-                    [MofangNode::Heavenly, MofangNode::Yang] => {
-                        PartialResult::Success(unsort(vec![Some(MofangNode::Yang), None]))
-                    }
-                    [MofangNode::Earthly, MofangNode::Yin] => {
-                        PartialResult::Success(unsort(vec![Some(MofangNode::Yin), None]))
-                    }
-                    */
+                    [MofangNode::Heavenly, MofangNode::Yang]
+                    | [MofangNode::Earthly, MofangNode::Yin] => match rules.celestial_matches {
+                        CelestialMatches::Off => PartialResult::Failure,
+                        CelestialMatches::Cancel => PartialResult::Success(vec![None, None]),
+                        CelestialMatches::KeepYinYang => {
+                            PartialResult::Success(unsort(vec![None, Some(sorted[1].clone())]))
+                        }
+                    },
                     // Human ingenuity can attract any element
                     [element, MofangNode::Human] if element.is_elemental() => {
                         PartialResult::Success(unsort(vec![None, Some((*element).clone())]))
@@ -149,125 +256,52 @@ impl Node for MofangNode {
         }
     }
 
-    fn new_game(seed: u64) -> Board<MofangNode> {
-        let radius = 11;
-        let rand = fastrand::Rng::new();
-
-        let mut bank = Self::standard_game();
-        rand.shuffle(&mut bank);
-
-        let mut out = Board::new(11);
-        out.nodes
-            .insert(Coordinate::new(0, 0), Some(MofangNode::Destruction));
-
-        let mut try_insert = |coord, node, req_neighbor| {
-            // Fail if:
-            // - there's something here
-            // - it's out of bounds
-            let failure = !out.in_bounds(coord) || out.get_node(coord).is_some()
-                // - there are no neighbors and we want some
-                || (req_neighbor
-                    && !coord
-                        .neighbors()
-                        .iter()
-                        .any(|&c| out.get_node(c).is_some()))
-                    // - this is qi and we have 2 neighbor qi
-                    || (matches!(node, MofangNode::Qi)
-                        && Direction::all().iter().filter(|&&dir| {
-                            matches!(out.get_node(coord + dir), Some(MofangNode::Qi))
-                        }).count() >= 2)
-                /*
-                    // - i am sandwiched by qi
-                    || Direction::all().iter().take(3).any(|&dir| {
-                        matches!(out.get_node(coord + dir), Some(Node::Qi))
-                            && matches!(out.get_node(coord - dir), Some(Node::Qi))
-                    })
-                */
-                ;
-            if failure {
-                Some(node)
-            } else {
-                out.nodes.insert(coord, Some(node));
-                None
-            }
+    fn points(mov: &Move<MofangNode>) -> u32 {
+        let bonus = if mov.before.contains(&MofangNode::Destruction) {
+            DESTRUCTION_BONUS
+        } else {
+            0
         };
+        score::cleared_points(mov) + bonus
+    }
 
-        // +1 => right
-        // -1 => left
-        let chirality = if rand.bool() { 1 } else { -1 };
-        // make the non-spokes surrounding the center always be qi
-        for idx in 0..3 {
-            let pos = Coordinate::new(0, -chirality).rotate_around_zero(Angle::from_int(idx * 2));
-            try_insert(pos, MofangNode::Qi, false);
-        }
-
-        'outer: for radius in 1..=radius {
-            let prob = if radius == 1 {
-                1.0
-            } else if radius % 2 == 1 {
-                0.8
-            } else {
-                0.0
-            };
-            // try each ring this many times
-            for _ in 0..3 {
-                let mut ring = Coordinate::new(0i32, 0)
-                    .ring_iter(radius, Spin::CW(Direction::XZ))
-                    .collect_vec();
-                rand.shuffle(&mut ring);
-                for coord in ring {
-                    let on_spoke = (coord.x == 0 && coord.y.signum() == chirality)
-                        || (coord.y == 0 && coord.z().signum() == chirality)
-                        || (coord.z() == 0 && coord.x.signum() == chirality);
-
-                    if on_spoke && radius == 0 {
-                        // Just place Qi
-                        try_insert(coord, MofangNode::Qi, false);
-                    }
-                    let prob = if on_spoke { 1.0 } else { prob };
-                    if rand.f32() <= prob {
-                        if let Some(node) = bank.pop() {
-                            let neighbor_req = radius as f32 / ((radius - 1) as f32);
-                            if let Some(failed_to_insert) =
-                                try_insert(coord, node, rand.f32() <= neighbor_req)
-                            {
-                                bank.push(failed_to_insert);
-                            }
-                        } else {
-                            break 'outer;
-                        }
-                    }
-                }
-            }
-        }
-        println!("remaining: {:?}", &bank);
-
-        let coord_options = Coordinate::new(0, 0).range_iter(radius).collect_vec();
-        while let Some(node) = bank.pop() {
-            // TODO: Not sure how to do this nicely since `try_insert` eats `node`,
-            // so I gave up and did it with a fold
-            let result = (0..1000).try_fold(node, |node, _| {
-                let rand_coord = coord_options[rand.usize(..coord_options.len())];
-                try_insert(rand_coord, node, true)
-            });
-            if result.is_some() {
-                // just give up and try again
-                println!("giving up...");
-                return Self::new_game(seed);
-            }
+    fn gen_options() -> GenOptions<MofangNode> {
+        GenOptions {
+            diameter: 11,
+            mask: None,
+            center: Some(MofangNode::Destruction),
+            around_center: vec![MofangNode::Qi; 3],
+            bank: Self::standard_game(),
+            inner_ring_fill: 1.0,
+            odd_ring_fill: 0.8,
+            even_ring_fill: 0.0,
+            rules: Default::default(),
+            tier: None,
         }
-        println!("remaining (must be empty): {:?}", &bank);
+    }
 
-        out
+    fn new_game_with(
+        seed: u64,
+        options: &GenOptions<MofangNode>,
+    ) -> Result<Board<MofangNode>, GenError> {
+        generation::layout(seed, options, |board, coord, node| {
+            // no qi with 2 neighbor qi
+            !(matches!(node, MofangNode::Qi)
+                && Direction::all()
+                    .iter()
+                    .filter(|&&dir| matches!(board.get_node(coord + dir), Some(MofangNode::Qi)))
+                    .count()
+                    >= 2)
+        })
     }
 }
 
 impl MofangNode {
     /// What's the number of contiguous open nodes required to be selectable?
-    fn freeness_req(&self) -> usize {
+    fn freeness_req(&self, rules: &MofangRules) -> usize {
         match self {
-            MofangNode::Qi => 5,
-            _ => 3,
+            MofangNode::Qi => rules.qi_freeness,
+            _ => rules.freeness,
         }
     }
 
@@ -291,11 +325,6 @@ impl MofangNode {
             MofangNode::Earth => Some(MofangNode::Water),
             MofangNode::Water => Some(MofangNode::Fire),
 
-            MofangNode::Heavenly => Some(MofangNode::Yang),
-            MofangNode::Yang => Some(MofangNode::Heavenly),
-            MofangNode::Earthly => Some(MofangNode::Yin),
-            MofangNode::Yin => Some(MofangNode::Earthly),
-
             MofangNode::Qi => Some(MofangNode::Qi),
             MofangNode::Creation => Some(MofangNode::Creation),
             _ => None,
@@ -327,34 +356,19 @@ impl MofangNode {
 
     /// Return the standard game sans 1 Destruction to go in the center
     /// and 3 qi to surround it.
-    fn standard_game() -> Vec<MofangNode> {
-        let mut game = vec![];
-
-        for element in &[
-            MofangNode::Wood,
-            MofangNode::Fire,
-            MofangNode::Earth,
-            MofangNode::Metal,
-            MofangNode::Water,
-        ] {
-            for _ in 0..7 {
-                game.push(element.clone());
-            }
-        }
-
-        for node in &[MofangNode::Heavenly, MofangNode::Earthly, MofangNode::Human] {
-            for _ in 0..4 {
-                game.push(node.clone());
-            }
-        }
-
-        game.push(MofangNode::Yin);
-        game.push(MofangNode::Yang);
-
-        for _ in 0..3 {
-            game.push(MofangNode::Qi);
-        }
-
-        game
+    fn standard_game() -> Vec<(MofangNode, usize)> {
+        vec![
+            (MofangNode::Wood, 7),
+            (MofangNode::Fire, 7),
+            (MofangNode::Earth, 7),
+            (MofangNode::Metal, 7),
+            (MofangNode::Water, 7),
+            (MofangNode::Heavenly, 4),
+            (MofangNode::Earthly, 4),
+            (MofangNode::Human, 4),
+            (MofangNode::Yin, 1),
+            (MofangNode::Yang, 1),
+            (MofangNode::Qi, 3),
+        ]
     }
 }
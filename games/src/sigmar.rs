@@ -1,10 +1,15 @@
 use enum_map::Enum;
-use hex2d::{Coordinate, Direction, Spin};
+use hex2d::Coordinate;
 use itertools::Itertools;
-use mofang_engine::{all_unique, Board, Node, PartialResult};
+use mofang_engine::{
+    all_unique, generation, score, Board, GenError, GenOptions, Move, Node, PartialResult,
+};
+
+/// Matching all four primes with Quintessence is worth this much on top of the usual points.
+pub const QUINTESSENCE_BONUS: u32 = 100;
 
 /// One of the marbles on the game board.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Enum)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Enum)]
 pub enum SigmarNode {
     Salt,
     Quintessence,
@@ -27,6 +32,8 @@ pub enum SigmarNode {
 }
 
 impl Node for SigmarNode {
+    type Rules = ();
+
     fn name() -> &'static str {
         "sigmar"
     }
@@ -51,6 +58,31 @@ impl Node for SigmarNode {
             SigmarNode::Mors => "mors",
         }
     }
+    fn index(&self) -> usize {
+        self.clone() as usize
+    }
+    fn from_texture_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "salt" => SigmarNode::Salt,
+            "quintessence" => SigmarNode::Quintessence,
+            "water" => SigmarNode::Water,
+            "earth" => SigmarNode::Earth,
+            "fire" => SigmarNode::Fire,
+            "air" => SigmarNode::Air,
+
+            "quicksilver" => SigmarNode::Quicksilver,
+            "lead" => SigmarNode::Lead,
+            "tin" => SigmarNode::Tin,
+            "iron" => SigmarNode::Iron,
+            "copper" => SigmarNode::Copper,
+            "silver" => SigmarNode::Silver,
+            "gold" => SigmarNode::Gold,
+
+            "vitae" => SigmarNode::Vitae,
+            "mors" => SigmarNode::Mors,
+            _ => return None,
+        })
+    }
     fn can_select(
         &self,
         board: &Board<SigmarNode>,
@@ -60,15 +92,19 @@ impl Node for SigmarNode {
         board.max_open_neighbors(coord) >= 3
             && self
                 .downgrade()
-                .and_then(|d| board.nodes_iter().find(|(_, n)| *n == Some(&d)))
+                .filter(|lower| board.count(lower) > 0)
                 .is_none()
     }
 
     /// Given a list of Nodes, see whether this pattern could exist
     /// and, if so, what to replace each Node with.
-    fn select(nodes: &[&SigmarNode]) -> PartialResult<Vec<Option<SigmarNode>>> {
+    fn select(nodes: &[&SigmarNode], _rules: &()) -> PartialResult<Vec<Option<SigmarNode>>> {
         match nodes.iter().sorted_unstable().as_slice() {
             [] => unreachable!("You can't select 0 nodes!"),
+            // Gold is the last metal and, like in Sigmar's Garden, goes away on its own once
+            // it's free. Without this the gold in the center could never be cleared,
+            // so no board was winnable and the solver could never prove one was.
+            [SigmarNode::Gold] => PartialResult::Success(vec![None]),
             [_] => PartialResult::Continue,
             [left, right] if left.cancels_with(right) => PartialResult::Success(vec![None, None]),
             [SigmarNode::Quintessence, rest @ ..]
@@ -84,89 +120,35 @@ impl Node for SigmarNode {
         }
     }
 
-    fn new_game(seed: u64) -> Board<SigmarNode> {
-        let radius = 11;
-        let rand = fastrand::Rng::new();
-
-        let mut bank = Self::standard_game(false);
-        rand.shuffle(&mut bank);
-
-        let mut out = Board::new(11);
-        out.nodes
-            .insert(Coordinate::new(0, 0), Some(SigmarNode::Gold));
-
-        let mut try_insert = |coord, node, req_neighbor| {
-            // Fail if:
-            // - there's something here
-            // - it's out of bounds
-            let failure = !out.in_bounds(coord)
-                || out.get_node(coord).is_some()
-                || (req_neighbor && !coord.neighbors().iter().any(|&c| out.get_node(c).is_some()));
-            if failure {
-                Some(node)
-            } else {
-                out.nodes.insert(coord, Some(node));
-                None
-            }
+    fn points(mov: &Move<SigmarNode>) -> u32 {
+        let bonus = if mov.before.contains(&SigmarNode::Quintessence) {
+            QUINTESSENCE_BONUS
+        } else {
+            0
         };
+        score::cleared_points(mov) + bonus
+    }
 
-        // +1 => right
-        // -1 => left
-        let chirality = if rand.bool() { 1 } else { -1 };
-        'outer: for radius in 1..=radius {
-            let prob = if radius == 1 {
-                1.0
-            } else if radius % 2 == 1 {
-                0.8
-            } else {
-                0.0
-            };
-            // try each ring this many times
-            for _ in 0..3 {
-                let mut ring = Coordinate::new(0i32, 0)
-                    .ring_iter(radius, Spin::CW(Direction::XZ))
-                    .collect_vec();
-                rand.shuffle(&mut ring);
-                for coord in ring {
-                    let on_spoke = (coord.x == 0 && coord.y.signum() == chirality)
-                        || (coord.y == 0 && coord.z().signum() == chirality)
-                        || (coord.z() == 0 && coord.x.signum() == chirality);
-
-                    let prob = if on_spoke { 1.0 } else { prob };
-                    if rand.f32() <= prob {
-                        if let Some(node) = bank.pop() {
-                            let neighbor_req = radius as f32 / ((radius - 1) as f32);
-                            if let Some(failed_to_insert) =
-                                try_insert(coord, node, rand.f32() <= neighbor_req)
-                            {
-                                bank.push(failed_to_insert);
-                            }
-                        } else {
-                            break 'outer;
-                        }
-                    }
-                }
-            }
+    fn gen_options() -> GenOptions<SigmarNode> {
+        GenOptions {
+            diameter: 11,
+            mask: None,
+            center: Some(SigmarNode::Gold),
+            around_center: Vec::new(),
+            bank: Self::standard_game(false),
+            inner_ring_fill: 1.0,
+            odd_ring_fill: 0.8,
+            even_ring_fill: 0.0,
+            rules: Default::default(),
+            tier: None,
         }
-        println!("remaining: {:?}", &bank);
-
-        let coord_options = Coordinate::new(0, 0).range_iter(radius).collect_vec();
-        while let Some(node) = bank.pop() {
-            // TODO: Not sure how to do this nicely since `try_insert` eats `node`,
-            // so I gave up and did it with a fold
-            let result = (0..1000).try_fold(node, |node, _| {
-                let rand_coord = coord_options[rand.usize(..coord_options.len())];
-                try_insert(rand_coord, node, true)
-            });
-            if result.is_some() {
-                // just give up and try again
-                println!("giving up...");
-                return Self::new_game(seed);
-            }
-        }
-        println!("remaining (must be empty): {:?}", &bank);
+    }
 
-        out
+    fn new_game_with(
+        seed: u64,
+        options: &GenOptions<SigmarNode>,
+    ) -> Result<Board<SigmarNode>, GenError> {
+        generation::layout(seed, options, |_, _, _| true)
     }
 }
 
@@ -209,26 +191,16 @@ impl SigmarNode {
     }
 
     /// Return the standard game sans 1 Gold to go in the center.
-    fn standard_game(aether: bool) -> Vec<SigmarNode> {
-        let mut game = vec![];
-
-        for element in &[
-            SigmarNode::Water,
-            SigmarNode::Earth,
-            SigmarNode::Fire,
-            SigmarNode::Air,
-        ] {
-            for _ in 0..8 {
-                game.push(element.clone());
-            }
-        }
-        for _ in 0..4 {
-            game.push(SigmarNode::Salt);
-        }
+    fn standard_game(aether: bool) -> Vec<(SigmarNode, usize)> {
+        let mut game = vec![
+            (SigmarNode::Water, 8),
+            (SigmarNode::Earth, 8),
+            (SigmarNode::Fire, 8),
+            (SigmarNode::Air, 8),
+            (SigmarNode::Salt, 4),
+        ];
         if aether {
-            for _ in 0..2 {
-                game.push(SigmarNode::Quintessence);
-            }
+            game.push((SigmarNode::Quintessence, 2));
         }
 
         for node in &[
@@ -238,8 +210,8 @@ impl SigmarNode {
             SigmarNode::Copper,
             SigmarNode::Silver,
         ] {
-            game.push(node.to_owned());
-            game.push(SigmarNode::Quicksilver);
+            game.push((node.to_owned(), 1));
+            game.push((SigmarNode::Quicksilver, 1));
         }
 
         game
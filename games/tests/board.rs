@@ -0,0 +1,178 @@
+use hex2d::Coordinate;
+use mofang_engine::{Board, Node, OutOfBounds};
+use mofang_games::{MofangNode, MofangRules, SigmarNode};
+
+/// Work out `max_open_neighbors` the long way, to check the board's bookkeeping.
+fn open_run<N: Node>(board: &Board<N>, at: Coordinate) -> usize {
+    let filled: Vec<_> = at
+        .neighbors()
+        .iter()
+        .map(|c| board.get_node(*c).is_some())
+        .collect();
+    if filled.iter().all(|f| !f) {
+        return 6;
+    }
+    (0..6)
+        .map(|start| {
+            (0..6)
+                .take_while(|step| !filled[(start + step) % 6])
+                .count()
+        })
+        .max()
+        .unwrap()
+}
+
+fn assert_caches_match<N: Node + PartialEq>(board: &Board<N>, kinds: &[N]) {
+    for (coord, _) in board.nodes_iter() {
+        assert_eq!(
+            board.max_open_neighbors(&coord),
+            open_run(board, coord),
+            "at {:?}",
+            coord
+        );
+    }
+    for kind in kinds {
+        let count = board.nodes_iter().filter(|(_, n)| *n == Some(kind)).count();
+        assert_eq!(board.count(kind), count);
+    }
+    let filled = board.nodes_iter().filter(|(_, n)| n.is_some()).count();
+    assert_eq!(board.node_count(), filled);
+}
+
+#[test]
+fn slots_match_the_hexagon() {
+    for diameter in &[1, 5, 11, 21] {
+        let board = Board::<MofangNode>::new(*diameter);
+        assert_eq!(board.nodes_iter().count() as i32, board.hex_count());
+        assert!(board.nodes_iter().all(|(coord, _)| board.in_bounds(coord)));
+    }
+
+    let board = Board::<MofangNode>::new(5);
+    assert!(board.in_bounds(Coordinate::new(2, -2)));
+    // Corners of the square but not the hexagon
+    assert!(!board.in_bounds(Coordinate::new(2, 2)));
+    assert!(!board.in_bounds(Coordinate::new(-2, -1)));
+    assert!(!board.in_bounds(Coordinate::new(3, 0)));
+}
+
+#[test]
+fn clones_dont_share_nodes() {
+    let mut board = Board::new(5);
+    board
+        .set_node(Coordinate::new(1, 0), Some(MofangNode::Fire))
+        .unwrap();
+    let copy = board.clone();
+    assert_eq!(
+        board.set_node(Coordinate::new(1, 0), None),
+        Ok(Some(MofangNode::Fire))
+    );
+    assert_eq!(
+        copy.get_node(Coordinate::new(1, 0)),
+        Some(&MofangNode::Fire)
+    );
+    assert_eq!(board.get_node(Coordinate::new(1, 0)), None);
+}
+
+#[test]
+fn open_neighbors_at_the_edge() {
+    let mut board = Board::new(3);
+    let center = Coordinate::new(0, 0);
+    assert_eq!(board.max_open_neighbors(&center), 6);
+
+    board.set_node(center, Some(MofangNode::Qi)).unwrap();
+    // Off the board counts as open
+    assert_eq!(board.max_open_neighbors(&Coordinate::new(1, 0)), 5);
+    assert_eq!(board.max_open_neighbors(&Coordinate::new(5, 5)), 6);
+
+    for coord in center.neighbors().iter().step_by(2) {
+        board.set_node(*coord, Some(MofangNode::Qi)).unwrap();
+    }
+    assert_eq!(board.max_open_neighbors(&center), 1);
+}
+
+#[test]
+fn off_the_board_is_an_error() {
+    let mut board = Board::new(5);
+    for &coord in &[
+        Coordinate::new(3, 0),
+        Coordinate::new(2, 2),
+        Coordinate::new(-100, 7),
+    ] {
+        assert_eq!(
+            board.set_node(coord, Some(MofangNode::Qi)),
+            Err(OutOfBounds(coord))
+        );
+        assert_eq!(board.set_node(coord, None), Err(OutOfBounds(coord)));
+    }
+    assert_eq!(
+        OutOfBounds(Coordinate::new(3, 0)).to_string(),
+        "(3, 0) is off the board"
+    );
+
+    // and the board is just like it was
+    let fresh = Board::<MofangNode>::new(5);
+    assert!(board.nodes_iter().eq(fresh.nodes_iter()));
+    assert_eq!(board.node_count(), 0);
+    assert!(!board.in_bounds(Coordinate::new(3, 0)));
+    assert_eq!(board.get_node(Coordinate::new(3, 0)), None);
+}
+
+#[test]
+fn builder_lays_out_boards() {
+    let rules = MofangRules {
+        freeness: 2,
+        ..MofangRules::default()
+    };
+    let board = Board::builder(5)
+        .rules(rules.clone())
+        .node(Coordinate::new(0, 0), MofangNode::Destruction)
+        .nodes(vec![
+            (Coordinate::new(1, 0), MofangNode::Fire),
+            (Coordinate::new(-1, 0), MofangNode::Water),
+        ])
+        .build()
+        .unwrap();
+    assert_eq!(board.diameter(), 5);
+    assert_eq!(board.rules(), &rules);
+    assert_eq!(board.node_count(), 3);
+    assert_eq!(
+        board.get_node(Coordinate::new(-1, 0)),
+        Some(&MofangNode::Water)
+    );
+
+    // The first node that doesn't fit is the one that gets reported
+    let result = Board::builder(5)
+        .node(Coordinate::new(0, 0), MofangNode::Destruction)
+        .node(Coordinate::new(0, 3), MofangNode::Fire)
+        .node(Coordinate::new(4, 0), MofangNode::Fire)
+        .build();
+    assert_eq!(result.err(), Some(OutOfBounds(Coordinate::new(0, 3))));
+}
+
+#[test]
+fn caches_keep_up_with_set_node() {
+    let kinds = [SigmarNode::Gold, SigmarNode::Salt, SigmarNode::Water];
+    let mut board = SigmarNode::new_game(5).unwrap();
+    assert_caches_match(&board, &kinds);
+
+    let rand = fastrand::Rng::with_seed(5);
+    let coords: Vec<_> = board.nodes_iter().map(|(coord, _)| coord).collect();
+    for _ in 0..500 {
+        let coord = coords[rand.usize(..coords.len())];
+        let node = match rand.u8(..4) {
+            0 => None,
+            idx => Some(kinds[idx as usize - 1].clone()),
+        };
+        board.set_node(coord, node).unwrap();
+    }
+    assert_caches_match(&board, &kinds);
+
+    // Clones keep their own books
+    let copy = board.clone();
+    for coord in coords {
+        board.set_node(coord, None).unwrap();
+    }
+    assert_caches_match(&board, &kinds);
+    assert_eq!(board.node_count(), 0);
+    assert_caches_match(&copy, &kinds);
+}
@@ -0,0 +1,41 @@
+//! Boards and games that more than one test file plays with.
+//!
+//! Every test file is its own crate and only uses some of these.
+#![allow(dead_code)]
+
+use hex2d::Coordinate;
+use mofang_engine::{Board, Game, Node};
+use mofang_games::MofangNode;
+
+/// Flatten a board into something we can compare and print.
+pub fn layout<N: Node + Clone>(board: &Board<N>) -> Vec<(Coordinate, Option<N>)> {
+    board
+        .nodes_iter()
+        .map(|(coord, node)| (coord, node.cloned()))
+        .collect()
+}
+
+/// A hexagonal board this many hexes across with only these nodes on it.
+pub fn board_with<N: Node + Clone>(diameter: i32, nodes: &[((i32, i32), N)]) -> Board<N> {
+    Board::builder(diameter)
+        .nodes(
+            nodes
+                .iter()
+                .map(|((x, y), node)| (Coordinate::new(*x, *y), node.clone())),
+        )
+        .build()
+        .unwrap()
+}
+
+/// A new game on `board_with`.
+pub fn game_with<N: Node + Clone>(diameter: i32, nodes: &[((i32, i32), N)]) -> Game<N> {
+    Game::new(board_with(diameter, nodes))
+}
+
+/// A Fire and a Metal with room around them, so it's one combo to clear.
+pub fn fire_and_metal() -> Board<MofangNode> {
+    board_with(
+        7,
+        &[((0, 0), MofangNode::Fire), ((2, 0), MofangNode::Metal)],
+    )
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use hex2d::Coordinate;
+use mofang_engine::{
+    daily::{self, DailyHistory, Date},
+    Game, Node,
+};
+use mofang_games::MofangNode;
+
+use std::time::Duration;
+
+fn date(year: i32, month: u32, day: u32) -> Date {
+    Date::new(year, month, day).unwrap()
+}
+
+#[test]
+fn dates_count_days() {
+    assert_eq!(date(1970, 1, 1).days_since_epoch(), 0);
+    assert_eq!(date(2000, 3, 1).days_since_epoch(), 11017);
+    assert_eq!(date(1969, 12, 31).days_since_epoch(), -1);
+    for days in -1000..20000 {
+        assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+    }
+    assert_eq!(date(2020, 2, 28).add_days(1), date(2020, 2, 29));
+    assert_eq!(date(2021, 2, 28).add_days(1), date(2021, 3, 1));
+    assert_eq!(date(2021, 12, 31).add_days(1), date(2022, 1, 1));
+
+    // Thursday, then a Monday
+    assert_eq!(date(1970, 1, 1).weekday(), 3);
+    assert_eq!(date(2021, 6, 7).weekday(), 0);
+}
+
+#[test]
+fn dates_read_and_write() {
+    assert_eq!(date(2021, 6, 1).to_string(), "2021-06-01");
+    assert_eq!("2021-06-01".parse(), Ok(date(2021, 6, 1)));
+    assert!("2021-02-29".parse::<Date>().is_err());
+    assert!("2021-13-01".parse::<Date>().is_err());
+    assert!("yesterday".parse::<Date>().is_err());
+    assert!(Date::new(2000, 2, 29).is_some());
+    assert!(Date::new(1900, 2, 29).is_none());
+}
+
+#[test]
+fn daily_seeds_are_stable() {
+    // This has to be the same everywhere, forever, or people get different dailies
+    assert_eq!(daily::seed("mofang", date(2021, 6, 1)), 8442707807362285493);
+    assert_ne!(
+        daily::seed("mofang", date(2021, 6, 1)),
+        daily::seed("sigmar", date(2021, 6, 1))
+    );
+    assert_ne!(
+        daily::seed("mofang", date(2021, 6, 1)),
+        daily::seed("mofang", date(2021, 6, 2))
+    );
+}
+
+fn cleared(secs: u64) -> Game<MofangNode> {
+    let mut game = common::game_with(
+        5,
+        &[((-1, 0), MofangNode::Fire), ((1, 0), MofangNode::Metal)],
+    );
+    game.add_time(Duration::from_secs(secs));
+    game.click(Coordinate::new(-1, 0));
+    game.click(Coordinate::new(1, 0));
+    game
+}
+
+#[test]
+fn only_the_first_clear_counts() {
+    let day = date(2021, 6, 1);
+    let mut history = DailyHistory::new();
+    assert!(history.record("alice", &cleared(90), day));
+    assert!(!history.record("alice", &cleared(30), day));
+    assert!(history.record("bob", &cleared(30), day));
+    assert!(history.record("alice", &cleared(60), day.add_days(1)));
+
+    let result = history.get("alice", "mofang", day).unwrap();
+    assert_eq!(result.time, Duration::from_secs(90));
+    assert_eq!(result.moves, 1);
+    assert!(history.get("alice", "sigmar", day).is_none());
+
+    // Unfinished games don't count
+    let unfinished = Game::new(MofangNode::new_game(1).unwrap());
+    assert!(!history.record("carol", &unfinished, day));
+    // Neither do ones won with help
+    let mut helped = cleared(10);
+    helped.mark_assisted();
+    assert!(!history.record("carol", &helped, day));
+    assert!(history.get("carol", "mofang", day).is_none());
+
+    let loaded = DailyHistory::read(&history.write()).unwrap();
+    assert_eq!(loaded, history);
+    assert!(DailyHistory::read("mofang-daily 1\ndaily alice mofang today 1 1").is_err());
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::fire_and_metal;
+use mofang_engine::{
+    difficulty::{self, Analyzer, Tier},
+    unmatch, Node, Solver,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+#[test]
+fn tiers_read_and_write() {
+    for tier in Tier::ALL.iter() {
+        assert_eq!(tier.to_string().parse(), Ok(*tier));
+    }
+    assert_eq!("hard".parse(), Ok(Tier::Hard));
+    assert!("brutal".parse::<Tier>().is_err());
+    assert!(Tier::of_score(0.0) < Tier::of_score(100.0));
+}
+
+#[test]
+fn one_combo_is_easy() {
+    let difficulty = Analyzer::default().rate(&fire_and_metal());
+    assert_eq!(difficulty.solutions, 1);
+    assert_eq!(difficulty.branching, vec![1]);
+    assert_eq!(difficulty.playout_wins, 1.0);
+    assert_eq!(difficulty.tier, Tier::Easy);
+}
+
+#[test]
+fn ratings_dont_change() {
+    let (board, line) = Solver::default().solvable_game::<SigmarNode>(3).unwrap();
+    let analyzer = Analyzer::default();
+    let difficulty = analyzer.rate_solved(&board, &line);
+    assert_eq!(analyzer.rate_solved(&board, &line), difficulty);
+    assert_eq!(difficulty.branching.len(), line.len());
+    assert!((0.0..=100.0).contains(&difficulty.score));
+    assert_eq!(difficulty.tier, Tier::of_score(difficulty.score));
+    // Playouts are seeded from the board, so this is the same on every machine
+    assert_eq!(difficulty.playout_wins, 0.175);
+}
+
+#[test]
+fn generation_hits_the_tier() {
+    let mut options = SigmarNode::gen_options();
+    options.tier = Some(Tier::Hard);
+    let (board, line) = Solver::default().solvable_game_with(7, &options).unwrap();
+    let difficulty = Analyzer::default().rate_solved(&board, &line);
+    assert_eq!(difficulty.tier, Tier::Hard);
+}
+
+#[test]
+fn unmatched_generation_hits_the_tier() {
+    let mut options = MofangNode::gen_options();
+    options.tier = Some(Tier::Easy);
+    let (board, line) = unmatch::generate(5, &options).unwrap();
+    let difficulty = Analyzer::default().rate_solved(&board, &line);
+    assert_eq!(difficulty.tier, Tier::Easy);
+}
+
+#[test]
+fn seeds_in_a_tier_make_boards_in_that_tier() {
+    let seed = difficulty::seed_in_tier::<SigmarNode>(11, Tier::Easy).unwrap();
+    let (board, line) = Solver::default().solvable_game::<SigmarNode>(seed).unwrap();
+    let again = SigmarNode::new_solvable_game(seed).unwrap();
+    assert!(board.nodes_iter().eq(again.nodes_iter()));
+    assert_eq!(
+        Analyzer::default().rate_solved(&board, &line).tier,
+        Tier::Easy
+    );
+    // Same start, same answer
+    assert_eq!(
+        difficulty::seed_in_tier::<SigmarNode>(11, Tier::Easy),
+        Some(seed)
+    );
+}
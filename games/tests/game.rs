@@ -0,0 +1,157 @@
+mod common;
+
+use hex2d::Coordinate;
+use mofang_engine::{Click, Game, Solver, Status};
+use mofang_games::MofangNode;
+
+/// The Wood is left over once the Fire and Metal go.
+fn fire_metal_and_wood() -> Game<MofangNode> {
+    common::game_with(
+        5,
+        &[
+            ((-1, 0), MofangNode::Fire),
+            ((1, 0), MofangNode::Metal),
+            ((0, 1), MofangNode::Wood),
+        ],
+    )
+}
+
+#[test]
+fn clicking_a_pair_applies_it() {
+    let mut game = fire_metal_and_wood();
+    assert_eq!(game.click(Coordinate::new(-1, 0)), Click::Selection);
+    assert_eq!(game.selected(), &[Coordinate::new(-1, 0)]);
+    assert_eq!(game.click(Coordinate::new(1, 0)), Click::Matched);
+
+    assert!(game.selected().is_empty());
+    assert_eq!(game.board().get_node(Coordinate::new(-1, 0)), None);
+    assert_eq!(game.board().get_node(Coordinate::new(1, 0)), None);
+    assert_eq!(game.history().len(), 1);
+    assert_eq!(
+        game.history()[0].before,
+        vec![MofangNode::Fire, MofangNode::Metal]
+    );
+    // The Wood has nothing left to go with
+    assert_eq!(game.status(), Status::Lost);
+}
+
+#[test]
+fn mismatches_cant_be_selected() {
+    let mut game = fire_metal_and_wood();
+    game.click(Coordinate::new(-1, 0));
+    assert!(!game.is_selectable(Coordinate::new(0, 1)));
+    assert_eq!(game.click(Coordinate::new(0, 1)), Click::Ignored);
+    assert_eq!(game.selected(), &[Coordinate::new(-1, 0)]);
+}
+
+#[test]
+fn clicking_again_deselects() {
+    let mut game = fire_metal_and_wood();
+    game.click(Coordinate::new(-1, 0));
+    assert_eq!(game.click(Coordinate::new(-1, 0)), Click::Selection);
+    assert!(game.selected().is_empty());
+
+    game.click(Coordinate::new(-1, 0));
+    assert_eq!(game.click(Coordinate::new(0, -2)), Click::Selection);
+    assert!(game.selected().is_empty());
+}
+
+#[test]
+fn clearing_the_board_wins() {
+    let mut game = common::game_with(5, &[((-2, 0), MofangNode::Yin), ((2, 0), MofangNode::Yang)]);
+
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    assert_eq!(
+        game.board().get_node(Coordinate::new(2, 0)),
+        Some(&MofangNode::Creation)
+    );
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(game.click(Coordinate::new(0, 0)), Click::Ignored);
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut game = fire_metal_and_wood();
+    assert!(!game.undo());
+    game.click(Coordinate::new(-1, 0));
+    game.click(Coordinate::new(1, 0));
+
+    assert!(game.undo());
+    assert!(game.history().is_empty());
+    assert_eq!(
+        game.board().get_node(Coordinate::new(-1, 0)),
+        Some(&MofangNode::Fire)
+    );
+    assert_eq!(
+        game.board().get_node(Coordinate::new(1, 0)),
+        Some(&MofangNode::Metal)
+    );
+
+    assert!(game.redo());
+    assert!(!game.redo());
+    assert_eq!(game.history().len(), 1);
+    assert_eq!(game.board().get_node(Coordinate::new(-1, 0)), None);
+    assert_eq!(game.board().get_node(Coordinate::new(1, 0)), None);
+}
+
+#[test]
+fn undo_takes_back_a_win() {
+    let mut game = common::game_with(5, &[((-2, 0), MofangNode::Qi), ((2, 0), MofangNode::Qi)]);
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    assert_eq!(game.status(), Status::Won);
+
+    game.undo();
+    assert_eq!(game.status(), Status::Playing);
+}
+
+#[test]
+fn new_moves_forget_the_redo_stack() {
+    let mut game = common::game_with(5, &[((-2, 0), MofangNode::Yin), ((2, 0), MofangNode::Yang)]);
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    game.undo();
+    assert_eq!(game.undone().len(), 1);
+
+    game.click(Coordinate::new(2, 0));
+    game.click(Coordinate::new(-2, 0));
+    assert!(game.undone().is_empty());
+    assert!(!game.redo());
+}
+
+#[test]
+fn hints_are_legal_combos() {
+    let mut game = fire_metal_and_wood();
+    let combos = game.legal_combos();
+    let hint = game.hint(&Solver::default()).unwrap();
+    assert!(combos.contains(&hint));
+
+    for coord in hint {
+        game.click(coord);
+    }
+    assert_eq!(game.history().len(), 1);
+}
+
+#[test]
+fn getting_stuck_loses() {
+    let mut game = fire_metal_and_wood();
+    assert_eq!(game.status(), Status::Playing);
+    game.click(Coordinate::new(-1, 0));
+    game.click(Coordinate::new(1, 0));
+    assert_eq!(game.status(), Status::Lost);
+    assert!(game.legal_combos().is_empty());
+
+    // Can't keep playing, but can take it back
+    assert_eq!(game.click(Coordinate::new(0, 1)), Click::Ignored);
+    assert!(game.undo());
+    assert_eq!(game.status(), Status::Playing);
+}
+
+#[test]
+fn stuck_boards_load_as_lost() {
+    let game = common::game_with(5, &[((0, 0), MofangNode::Fire)]);
+    assert_eq!(game.status(), Status::Lost);
+}
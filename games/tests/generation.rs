@@ -0,0 +1,257 @@
+use hex2d::Coordinate;
+use mofang_engine::{
+    generation,
+    unmatch::{self, UnmatchError},
+    Board, GenError, GenOptions, Mask, Node, Solver,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+use std::cell::Cell;
+
+#[test]
+fn practice_boards() {
+    let options = GenOptions {
+        diameter: 7,
+        bank: vec![
+            (MofangNode::Wood, 2),
+            (MofangNode::Fire, 2),
+            (MofangNode::Earth, 2),
+            (MofangNode::Metal, 2),
+            (MofangNode::Water, 2),
+            (MofangNode::Yin, 1),
+            (MofangNode::Yang, 1),
+        ],
+        ..MofangNode::gen_options()
+    };
+    for seed in 0..10 {
+        let board = MofangNode::new_game_with(seed, &options).unwrap();
+        assert_eq!(board.diameter(), 7);
+        // the bank, the Destruction and the 3 Qi around it
+        assert_eq!(board.node_count(), 12 + 1 + 3, "seed {}", seed);
+        assert_eq!(board.count(&MofangNode::Wood), 2);
+        assert_eq!(
+            board.get_node(Coordinate::new(0, 0)),
+            Some(&MofangNode::Destruction)
+        );
+    }
+}
+
+#[test]
+fn marathon_boards() {
+    let mut options = SigmarNode::gen_options();
+    options.diameter = 21;
+    for (_, count) in options.bank.iter_mut() {
+        *count *= 2;
+    }
+    let board = SigmarNode::new_game_with(7, &options).unwrap();
+    assert_eq!(board.hex_count(), 331);
+    assert_eq!(board.node_count(), options.node_count());
+    assert_eq!(board.count(&SigmarNode::Water), 16);
+    assert_eq!(board.count(&SigmarNode::Quicksilver), 10);
+}
+
+#[test]
+fn custom_centers_and_counts() {
+    let mut options = SigmarNode::gen_options();
+    options.center = None;
+    options.set_count(SigmarNode::Quintessence, 2);
+    options.set_count(SigmarNode::Salt, 0);
+    options.set_count(SigmarNode::Vitae, 3);
+    options.set_count(SigmarNode::Mors, 3);
+    assert_eq!(options.node_count(), 46 - 4 + 2 + 6);
+
+    let board = SigmarNode::new_game_with(3, &options).unwrap();
+    assert_eq!(board.node_count(), options.node_count());
+    assert_eq!(board.count(&SigmarNode::Gold), 0);
+    assert_eq!(board.count(&SigmarNode::Salt), 0);
+    assert_eq!(board.count(&SigmarNode::Quintessence), 2);
+    assert_eq!(board.count(&SigmarNode::Vitae), 3);
+}
+
+#[test]
+fn options_are_seeded_too() {
+    let options = GenOptions {
+        diameter: 15,
+        odd_ring_fill: 0.5,
+        even_ring_fill: 0.3,
+        ..MofangNode::gen_options()
+    };
+    let layout = |board: Board<MofangNode>| {
+        board
+            .nodes_iter()
+            .map(|(coord, node)| (coord, node.cloned()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        layout(MofangNode::new_game_with(42, &options).unwrap()),
+        layout(MofangNode::new_game_with(42, &options).unwrap())
+    );
+    assert_eq!(
+        layout(MofangNode::new_game(42).unwrap()),
+        layout(MofangNode::new_game_with(42, &MofangNode::gen_options()).unwrap())
+    );
+}
+
+#[test]
+fn small_solvable_games() {
+    let options = GenOptions {
+        diameter: 9,
+        bank: vec![
+            (SigmarNode::Water, 2),
+            (SigmarNode::Fire, 2),
+            (SigmarNode::Air, 2),
+            (SigmarNode::Salt, 2),
+            (SigmarNode::Lead, 1),
+            (SigmarNode::Quicksilver, 1),
+        ],
+        ..SigmarNode::gen_options()
+    };
+    let (board, line) = Solver::default().solvable_game_with(5, &options).unwrap();
+    assert_eq!(board.diameter(), 9);
+    assert!(!line.is_empty());
+}
+
+#[test]
+fn hopeless_banks_run_out_of_attempts() {
+    // The gold goes away on its own, but nothing can ever match a lone salt
+    let options = GenOptions {
+        bank: vec![(SigmarNode::Salt, 1)],
+        ..SigmarNode::gen_options()
+    };
+    let solver = Solver {
+        max_repairs: 0,
+        max_attempts: 3,
+        ..Solver::default()
+    };
+    assert_eq!(
+        solver.solvable_game_with(5, &options).err(),
+        Some(GenError::Unsolvable { attempts: 3 })
+    );
+}
+
+#[test]
+fn overfull_banks_are_rejected() {
+    let options = GenOptions {
+        diameter: 5,
+        ..MofangNode::gen_options()
+    };
+    let overfull = GenError::TooManyNodes {
+        nodes: options.node_count(),
+        hexes: 19,
+    };
+    assert_eq!(options.validate(), Err(overfull));
+    assert_eq!(MofangNode::new_game_with(0, &options).err(), Some(overfull));
+    assert_eq!(
+        unmatch::generate(0, &options).err(),
+        Some(UnmatchError::Options(overfull))
+    );
+}
+
+#[test]
+fn boards_need_a_sensible_size() {
+    for &diameter in &[0, -3, 1000] {
+        let options = GenOptions {
+            diameter,
+            ..SigmarNode::gen_options()
+        };
+        assert_eq!(options.validate(), Err(GenError::BadDiameter(diameter)));
+        assert!(Solver::default().solvable_game_with(0, &options).is_err());
+    }
+    assert_eq!(SigmarNode::gen_options().validate(), Ok(()));
+}
+
+#[test]
+fn layout_respects_the_constraint() {
+    let mut options = SigmarNode::gen_options();
+    options.set_count(SigmarNode::Salt, 12);
+    options.around_center = vec![SigmarNode::Vitae, SigmarNode::Mors];
+    let lonely_salt = |board: &Board<SigmarNode>, coord: Coordinate, node: &SigmarNode| {
+        *node != SigmarNode::Salt
+            || coord
+                .neighbors()
+                .iter()
+                .all(|&c| board.get_node(c) != Some(&SigmarNode::Salt))
+    };
+
+    for seed in 0..5 {
+        let board = generation::layout(seed, &options, lonely_salt).unwrap();
+        assert_eq!(board.node_count(), options.node_count());
+        assert_eq!(board.count(&SigmarNode::Vitae), 1);
+        for (coord, node) in board.nodes_iter() {
+            if node == Some(&SigmarNode::Salt) {
+                assert!(coord
+                    .neighbors()
+                    .iter()
+                    .all(|&c| board.get_node(c) != Some(&SigmarNode::Salt)));
+            }
+        }
+        // Vitae and Mors hug the center
+        for (coord, node) in board.nodes_iter() {
+            if matches!(node, Some(SigmarNode::Vitae) | Some(SigmarNode::Mors)) {
+                assert_eq!(coord.distance(Coordinate::new(0, 0)), 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn too_many_around_the_center_are_rejected() {
+    let mut options = MofangNode::gen_options();
+    options.around_center = vec![MofangNode::Qi; 4];
+    assert_eq!(
+        MofangNode::new_game_with(0, &options).err(),
+        Some(GenError::CrowdedCenter(4))
+    );
+}
+
+#[test]
+fn layouts_give_up_when_nothing_fits() {
+    let options = SigmarNode::gen_options();
+    let nowhere = |_: &Board<SigmarNode>, _: Coordinate, _: &SigmarNode| false;
+    assert_eq!(
+        generation::layout(0, &options, nowhere).err(),
+        Some(GenError::Unplaceable)
+    );
+
+    // Two islands with nothing in between, so the second one can never be reached
+    let islands = Mask::from_coords(
+        (0..7)
+            .map(|x| Coordinate::new(x - 8, 0))
+            .chain((0..7).map(|x| Coordinate::new(x + 2, 0))),
+    );
+    let options = GenOptions {
+        mask: Some(islands),
+        center: None,
+        around_center: Vec::new(),
+        bank: vec![(SigmarNode::Salt, 12)],
+        ..SigmarNode::gen_options()
+    };
+    assert_eq!(
+        SigmarNode::new_game_with(0, &options).err(),
+        Some(GenError::Unplaceable)
+    );
+}
+
+#[test]
+fn vetoed_spots_around_the_center_keep_their_nodes() {
+    let mut options = SigmarNode::gen_options();
+    options.around_center = vec![SigmarNode::Vitae, SigmarNode::Mors];
+    for seed in 0..5 {
+        // Turn Vitae and Mors away the first time each comes up,
+        // which is when they try to go around the center
+        let turned_away = Cell::new(0);
+        let not_at_first = |_: &Board<SigmarNode>, _: Coordinate, node: &SigmarNode| {
+            if matches!(node, SigmarNode::Vitae | SigmarNode::Mors) && turned_away.get() < 2 {
+                turned_away.set(turned_away.get() + 1);
+                false
+            } else {
+                true
+            }
+        };
+        let board = generation::layout(seed, &options, not_at_first).unwrap();
+        assert_eq!(turned_away.get(), 2);
+        assert_eq!(board.node_count(), options.node_count());
+        assert_eq!(board.count(&SigmarNode::Vitae), 1);
+        assert_eq!(board.count(&SigmarNode::Mors), 1);
+    }
+}
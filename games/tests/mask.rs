@@ -0,0 +1,132 @@
+use hex2d::Coordinate;
+use mofang_engine::{mask::MaskError, save, unmatch, Board, Game, GenOptions, Mask, Node, Status};
+use mofang_games::{MofangNode, SigmarNode};
+
+const DONUT: &str = "
+  . . .
+ . . . .
+. . x . .
+ . . . .
+  . . .
+";
+
+fn mask_options<N: Node>(mask: Mask, options: GenOptions<N>) -> GenOptions<N> {
+    GenOptions {
+        mask: Some(mask),
+        ..options
+    }
+}
+
+#[test]
+fn parses_layouts() {
+    let mask = Mask::parse(DONUT).unwrap();
+    assert_eq!(mask, Mask::ring(5, 1));
+    assert_eq!(mask.len(), 18);
+    assert_eq!(mask.diameter(), 5);
+    assert!(!mask.contains(Coordinate::new(0, 0)));
+    assert!(mask.contains(Coordinate::new(1, 0)));
+    assert!(mask.contains(Coordinate::new(0, 1)));
+
+    let full = Mask::parse(" . .\n. o .\n . .").unwrap();
+    assert!(full.is_hexagon());
+    assert_eq!(full, Mask::hexagon(3));
+}
+
+#[test]
+fn rejects_bad_layouts() {
+    assert_eq!(Mask::parse(". . ."), Err(MaskError::NoCenter));
+    assert_eq!(Mask::parse("o . x"), Err(MaskError::TwoCenters));
+    assert_eq!(
+        Mask::parse("o .\n ..\n"),
+        Err(MaskError::Misaligned { line: 2, column: 3 })
+    );
+    assert_eq!(
+        Mask::parse("o #"),
+        Err(MaskError::Unknown {
+            line: 1,
+            column: 3,
+            found: '#'
+        })
+    );
+}
+
+#[test]
+fn shapes_have_the_right_hexes() {
+    assert_eq!(Mask::hexagon(11).len(), 91);
+    assert_eq!(Mask::ring(11, 3).len(), 91 - 7);
+    let triangle = Mask::triangle(6);
+    assert_eq!(triangle.len(), 21);
+    assert!(triangle.contains(Coordinate::new(0, 0)));
+    assert!(!triangle.is_hexagon());
+}
+
+#[test]
+fn holes_are_open() {
+    let board: Board<MofangNode> = Board::builder_with_mask(Mask::ring(5, 1))
+        .node(Coordinate::new(1, 0), MofangNode::Fire)
+        .build()
+        .unwrap();
+    assert_eq!(board.hex_count(), 18);
+    assert!(!board.in_bounds(Coordinate::new(0, 0)));
+    assert!(board
+        .clone()
+        .set_node(Coordinate::new(0, 0), Some(MofangNode::Fire))
+        .is_err());
+    // The hole in the middle is open space, same as off the edge
+    assert_eq!(board.max_open_neighbors(&Coordinate::new(0, 1)), 5);
+    assert_eq!(board.max_open_neighbors(&Coordinate::new(1, -1)), 5);
+    assert_eq!(board.nodes_iter().count(), 18);
+}
+
+fn check_layout<N: Node + Clone + PartialEq>(mask: Mask) {
+    let options = mask_options(mask.clone(), N::gen_options());
+    for seed in 0..5 {
+        let board = N::new_game_with(seed, &options).unwrap();
+        assert!(board.mask() == &mask);
+        assert_eq!(board.node_count(), options.node_count(), "seed {}", seed);
+    }
+}
+
+#[test]
+fn layouts_fill_masks() {
+    check_layout::<MofangNode>(Mask::ring(13, 3));
+    check_layout::<SigmarNode>(Mask::ring(13, 3));
+    check_layout::<SigmarNode>(Mask::triangle(16));
+}
+
+#[test]
+fn unmatching_fills_masks() {
+    let options = mask_options(Mask::ring(13, 3), SigmarNode::gen_options());
+    for seed in 0..3 {
+        let (board, line) = unmatch::generate(seed, &options).unwrap();
+        assert_eq!(board.node_count(), options.node_count());
+        let mut game = Game::new(board);
+        for coord in line.into_iter().flatten() {
+            game.click(coord);
+        }
+        assert_eq!(game.status(), Status::Won);
+    }
+}
+
+#[test]
+fn saves_keep_holes() {
+    let options = mask_options(Mask::ring(13, 3), MofangNode::gen_options());
+    let game = Game::new(MofangNode::new_game_with(4, &options).unwrap());
+    let text = save::write(&game);
+    assert!(text.contains("hole 0 0\n"));
+
+    let loaded: Game<MofangNode> = save::read(&text).unwrap();
+    assert!(loaded.board().mask() == game.board().mask());
+    assert!(loaded.board().nodes_iter().eq(game.board().nodes_iter()));
+
+    // Holes have to come before the nodes
+    assert!(save::read::<MofangNode>(
+        "mofang-save 2\ngame mofang\ndiameter 5\nnode 1 0 qi\nhole 0 0"
+    )
+    .is_err());
+    // and each hole only gets made once
+    assert!(matches!(
+        save::read::<MofangNode>("mofang-save 2\ngame mofang\ndiameter 5\nhole 1 0\nhole 1 0"),
+        Err(save::LoadError::OutOfBounds(_))
+    ));
+}
@@ -0,0 +1,194 @@
+use hex2d::Coordinate;
+use mofang_engine::{
+    save::{self, LoadError},
+    unmatch, Board, Click, Game, GenOptions, Node, PartialResult,
+};
+use mofang_games::{CelestialMatches, MofangNode, MofangRules};
+
+fn select(nodes: &[MofangNode], rules: &MofangRules) -> Option<Vec<Option<MofangNode>>> {
+    let nodes: Vec<_> = nodes.iter().collect();
+    match MofangNode::select(&nodes, rules) {
+        PartialResult::Success(change) => Some(change),
+        _ => None,
+    }
+}
+
+#[test]
+fn qi_and_elements_are_off_by_default() {
+    let rules = MofangRules::default();
+    assert_eq!(select(&[MofangNode::Fire, MofangNode::Qi], &rules), None);
+
+    let rules = MofangRules {
+        qi_matches_elements: true,
+        ..MofangRules::default()
+    };
+    // The element turns into Qi, in whichever order they were picked
+    assert_eq!(
+        select(&[MofangNode::Fire, MofangNode::Qi], &rules),
+        Some(vec![Some(MofangNode::Qi), None])
+    );
+    assert_eq!(
+        select(&[MofangNode::Qi, MofangNode::Water], &rules),
+        Some(vec![None, Some(MofangNode::Qi)])
+    );
+    assert_eq!(select(&[MofangNode::Human, MofangNode::Qi], &rules), None);
+}
+
+#[test]
+fn celestial_match_variants() {
+    let pairs = [
+        [MofangNode::Heavenly, MofangNode::Yang],
+        [MofangNode::Yin, MofangNode::Earthly],
+    ];
+    let with = |celestial_matches| MofangRules {
+        celestial_matches,
+        ..MofangRules::default()
+    };
+
+    for pair in &pairs {
+        assert_eq!(select(pair, &with(CelestialMatches::Off)), None);
+        assert_eq!(
+            select(pair, &with(CelestialMatches::Cancel)),
+            Some(vec![None, None])
+        );
+    }
+    assert_eq!(
+        select(&pairs[0], &with(CelestialMatches::KeepYinYang)),
+        Some(vec![None, Some(MofangNode::Yang)])
+    );
+    assert_eq!(
+        select(&pairs[1], &with(CelestialMatches::KeepYinYang)),
+        Some(vec![Some(MofangNode::Yin), None])
+    );
+}
+
+#[test]
+fn freeness_comes_from_the_rules() {
+    // A Qi with a neighbor on one side has a run of 5 open neighbors
+    let mut board = Board::new(7);
+    board
+        .set_node(Coordinate::new(0, 0), Some(MofangNode::Qi))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(1, 0), Some(MofangNode::Fire))
+        .unwrap();
+    let qi = Coordinate::new(0, 0);
+    assert!(MofangNode::Qi.can_select(&board, &qi, &[]));
+
+    board
+        .set_node(Coordinate::new(-1, 0), Some(MofangNode::Fire))
+        .unwrap();
+    assert!(!MofangNode::Qi.can_select(&board, &qi, &[]));
+
+    board.set_rules(MofangRules {
+        qi_freeness: 1,
+        ..MofangRules::default()
+    });
+    assert!(MofangNode::Qi.can_select(&board, &qi, &[]));
+}
+
+#[test]
+fn games_play_by_their_boards_rules() {
+    let mut board = Board::new(7);
+    board
+        .set_node(Coordinate::new(-3, 0), Some(MofangNode::Qi))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(3, 0), Some(MofangNode::Wood))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(0, 3), Some(MofangNode::Qi))
+        .unwrap();
+    board.set_rules(MofangRules {
+        qi_matches_elements: true,
+        ..MofangRules::default()
+    });
+    let mut game = Game::new(board);
+
+    game.click(Coordinate::new(-3, 0));
+    assert_eq!(game.click(Coordinate::new(3, 0)), Click::Matched);
+    assert_eq!(
+        game.board().get_node(Coordinate::new(3, 0)),
+        Some(&MofangNode::Qi)
+    );
+    assert_eq!(game.board().get_node(Coordinate::new(-3, 0)), None);
+}
+
+#[test]
+fn rules_read_and_write() {
+    let rules = MofangRules {
+        qi_matches_elements: true,
+        celestial_matches: CelestialMatches::KeepYinYang,
+        freeness: 2,
+        qi_freeness: 4,
+        human_freeness: 1,
+    };
+    let words = MofangNode::write_rules(&rules);
+    let words: Vec<_> = words.iter().map(String::as_str).collect();
+    assert_eq!(MofangNode::read_rules(&words), Some(rules));
+
+    // Anything left out is the default
+    assert_eq!(
+        MofangNode::read_rules(&["freeness=2"]),
+        Some(MofangRules {
+            freeness: 2,
+            ..MofangRules::default()
+        })
+    );
+    assert_eq!(MofangNode::read_rules(&["freeness=lots"]), None);
+    assert_eq!(
+        MofangNode::read_rules(&["celestial_matches=sometimes"]),
+        None
+    );
+    assert_eq!(MofangNode::read_rules(&["gravity=off"]), None);
+}
+
+#[test]
+fn saves_keep_the_rules() {
+    let board = Board::builder(7)
+        .nodes(vec![
+            (Coordinate::new(-3, 0), MofangNode::Heavenly),
+            (Coordinate::new(3, 0), MofangNode::Yang),
+        ])
+        .rules(MofangRules {
+            celestial_matches: CelestialMatches::KeepYinYang,
+            ..MofangRules::default()
+        })
+        .build()
+        .unwrap();
+    let mut game = Game::new(board);
+    game.click(Coordinate::new(-3, 0));
+    assert_eq!(game.click(Coordinate::new(3, 0)), Click::Matched);
+
+    let text = save::write(&game);
+    assert!(text.contains("celestial_matches=keep_yin_yang"));
+    let loaded: Game<MofangNode> = save::read(&text).unwrap();
+    assert_eq!(loaded.board().rules(), game.board().rules());
+
+    // Under the standard rules the Yang wouldn't have stayed behind
+    let standard = text.replace(
+        "celestial_matches=keep_yin_yang",
+        "celestial_matches=cancel",
+    );
+    assert!(matches!(
+        save::read::<MofangNode>(&standard),
+        Err(LoadError::Inconsistent { .. })
+    ));
+}
+
+#[test]
+fn generated_boards_get_the_options_rules() {
+    let rules = MofangRules {
+        qi_matches_elements: true,
+        ..MofangRules::default()
+    };
+    let options = GenOptions {
+        rules: rules.clone(),
+        ..MofangNode::gen_options()
+    };
+    assert_eq!(
+        MofangNode::new_game_with(1, &options).unwrap().rules(),
+        &rules
+    );
+    assert_eq!(unmatch::generate(1, &options).unwrap().0.rules(), &rules);
+}
@@ -0,0 +1,260 @@
+mod common;
+
+use common::fire_and_metal;
+use hex2d::Coordinate;
+use mofang_engine::{
+    generation::GENERATOR_VERSION,
+    replay::{Action, Invalid, Replay},
+    save, Click, Game, Solver, Status,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+use std::time::Duration;
+
+/// Clear the board, with some dithering on the way.
+fn played() -> Game<MofangNode> {
+    let mut game = Game::seeded(fire_and_metal(), 9);
+    game.add_time(Duration::from_millis(1500));
+    game.click(Coordinate::new(0, 0));
+    // Nothing there, so this doesn't count
+    game.click(Coordinate::new(3, 3));
+    game.add_time(Duration::from_millis(500));
+    game.click(Coordinate::new(2, 0));
+    game.undo();
+    game.add_time(Duration::from_millis(250));
+    game.redo();
+    game
+}
+
+#[test]
+fn games_record_what_was_done() {
+    let game = played();
+    let actions: Vec<_> = game.steps().iter().map(|step| step.action).collect();
+    assert_eq!(
+        actions,
+        vec![
+            Action::Click(Coordinate::new(0, 0)),
+            Action::Click(Coordinate::new(2, 0)),
+            Action::Undo,
+            Action::Redo,
+        ]
+    );
+    let times: Vec<_> = game
+        .steps()
+        .iter()
+        .map(|step| step.at.as_millis())
+        .collect();
+    assert_eq!(times, vec![1500, 2000, 2000, 2250]);
+}
+
+#[test]
+fn replays_play_back_the_same_game() {
+    let replay = Replay::of(&played()).unwrap();
+    assert_eq!((replay.game.as_str(), replay.seed), ("mofang", 9));
+    assert_eq!(replay.length(), Duration::from_millis(2250));
+
+    let mut game = Game::seeded(fire_and_metal(), replay.seed);
+    for step in &replay.steps {
+        assert!(step.action.apply(&mut game));
+    }
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(game.moves(), 2);
+}
+
+#[test]
+fn replays_round_trip() {
+    let replay = Replay::of(&played()).unwrap();
+    let text = replay.write();
+    assert!(text.contains("step 2000 undo\n"));
+    assert_eq!(Replay::read(&text).unwrap(), replay);
+
+    // Saves keep the steps too, so resumed games still replay from the start
+    let loaded: Game<MofangNode> = save::read(&save::write(&played())).unwrap();
+    assert_eq!(loaded.steps(), played().steps());
+
+    assert!(Replay::of(&Game::new(fire_and_metal())).is_none());
+    assert!(Replay::read("mofang-replay 1\ngame mofang").is_err());
+    assert!(Replay::read("mofang-replay 1\ngame mofang\nseed 9\nstep 10 jump").is_err());
+    assert!(Replay::read("mofang-replay 3\ngame mofang\nseed 9").is_err());
+}
+
+#[test]
+fn replays_keep_their_board() {
+    // Not the board seed 9 makes, so it has to come from the replay
+    let replay = Replay::read(&Replay::of(&played()).unwrap().write()).unwrap();
+    let board = replay.board::<MofangNode>().unwrap();
+    assert!(board.nodes_iter().eq(fire_and_metal().nodes_iter()));
+
+    let mut broken = replay;
+    broken.board.push("node 0 0 gold".to_owned());
+    assert!(broken.board::<MofangNode>().is_err());
+}
+
+#[test]
+fn replays_need_the_whole_game() {
+    // Saves from before version 5 didn't keep the steps
+    let text: String = save::write(&played())
+        .lines()
+        .filter(|line| !line.starts_with("step"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let resumed: Game<MofangNode> = save::read(&text).unwrap();
+    assert_eq!(resumed.history().len(), 1);
+    assert!(Replay::of(&resumed).is_none());
+}
+
+#[test]
+fn replays_only_load_into_their_own_game() {
+    let replay = Replay::of(&played()).unwrap();
+    assert!(replay.board::<SigmarNode>().is_err());
+}
+
+/// A real clear of a seed, one second per click.
+fn cleared(seed: u64) -> Replay {
+    let (board, line) = Solver::default().solvable_game::<MofangNode>(seed).unwrap();
+    let mut game = Game::seeded(board, seed);
+    for coord in line.into_iter().flatten() {
+        game.add_time(Duration::from_secs(1));
+        assert_ne!(game.click(coord), Click::Ignored);
+    }
+    assert_eq!(game.status(), Status::Won);
+    Replay::of(&game).unwrap()
+}
+
+#[test]
+fn real_clears_verify() {
+    let replay = cleared(3);
+    let score = replay.verify::<MofangNode>(3).unwrap();
+    assert_eq!(score.elapsed, replay.length());
+    assert_eq!(
+        score.elapsed,
+        Duration::from_secs(replay.steps.len() as u64)
+    );
+
+    // Stopping short is legal, but doesn't count
+    let mut short = replay.clone();
+    short.steps.pop();
+    assert!(matches!(
+        short.verify::<MofangNode>(3),
+        Err(Invalid::NotCleared { left }) if left > 0
+    ));
+}
+
+#[test]
+fn replays_verify_on_a_board_made_once() {
+    let board = Solver::default().solvable_game::<MofangNode>(3).unwrap().0;
+    let replay = cleared(3);
+    assert_eq!(replay.verify_on(&board), replay.verify::<MofangNode>(3));
+    assert_eq!(
+        replay.verify_on(&fire_and_metal()),
+        Err(Invalid::WrongBoard)
+    );
+}
+
+#[test]
+fn replays_from_other_generators_dont_verify() {
+    let mut replay = cleared(3);
+    assert_eq!(replay.generator, GENERATOR_VERSION);
+    replay.generator += 1;
+    assert_eq!(
+        replay.verify::<MofangNode>(3),
+        Err(Invalid::WrongGenerator {
+            expected: GENERATOR_VERSION,
+            found: GENERATOR_VERSION + 1
+        })
+    );
+    assert_eq!(Replay::read(&replay.write()).unwrap(), replay);
+
+    // Replays that don't say are from the first one
+    let old = Replay::read("mofang-replay 1\ngame mofang\nseed 3").unwrap();
+    assert_eq!(old.generator, 1);
+}
+
+#[test]
+fn boards_are_compared_not_their_lines() {
+    // Same board, written down in a different order
+    let mut shuffled = cleared(3);
+    let first = shuffled
+        .board
+        .iter()
+        .position(|line| line.starts_with("node "))
+        .unwrap();
+    shuffled.board[first..].reverse();
+    assert!(shuffled.verify::<MofangNode>(3).is_ok());
+
+    let mut garbled = cleared(3);
+    garbled.board.push("node 0 0 pebble".to_owned());
+    assert_eq!(garbled.verify::<MofangNode>(3), Err(Invalid::WrongBoard));
+}
+
+#[test]
+fn old_replays_verify_on_the_seeds_board() {
+    let mut replay = cleared(3);
+    replay.board.clear();
+    assert!(replay.verify::<MofangNode>(3).is_ok());
+}
+
+#[test]
+fn tampered_replays_dont_verify() {
+    let replay = cleared(3);
+
+    // Taking a node off means it's not the seed's board anymore
+    let mut moved = replay.clone();
+    let node = moved
+        .board
+        .iter()
+        .position(|line| line.starts_with("node "))
+        .unwrap();
+    moved.board.remove(node);
+    assert_eq!(moved.verify::<MofangNode>(3), Err(Invalid::WrongBoard));
+
+    assert_eq!(
+        replay.verify::<MofangNode>(4),
+        Err(Invalid::WrongSeed {
+            expected: 4,
+            found: 3
+        })
+    );
+    assert!(matches!(
+        replay.verify::<SigmarNode>(3),
+        Err(Invalid::WrongGame(_))
+    ));
+
+    // Skipping a click leaves the next one unselectable or the combo wrong somewhere
+    let mut skipped = replay.clone();
+    skipped.steps.remove(0);
+    assert!(matches!(
+        skipped.verify::<MofangNode>(3),
+        Err(Invalid::Illegal { .. }) | Err(Invalid::NotCleared { .. })
+    ));
+
+    // Clicking after the board is cleared can't happen
+    let mut extra = replay.clone();
+    let last = *extra.steps.last().unwrap();
+    extra.steps.push(last);
+    let index = extra.steps.len() - 1;
+    assert_eq!(
+        extra.verify::<MofangNode>(3),
+        Err(Invalid::Illegal { index, step: last })
+    );
+
+    let mut redo_nothing = replay.clone();
+    redo_nothing.steps[0].action = Action::Redo;
+    assert_eq!(
+        redo_nothing.verify::<MofangNode>(3),
+        Err(Invalid::Illegal {
+            index: 0,
+            step: redo_nothing.steps[0]
+        })
+    );
+
+    let mut backwards = replay;
+    backwards.steps[2].at = Duration::from_millis(10);
+    assert_eq!(
+        backwards.verify::<MofangNode>(3),
+        Err(Invalid::OutOfOrder {
+            index: 2,
+            step: backwards.steps[2]
+        })
+    );
+}
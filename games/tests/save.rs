@@ -0,0 +1,157 @@
+mod common;
+
+use common::layout;
+use hex2d::Coordinate;
+use mofang_engine::{save, save::LoadError, Board, Game, Node, Status};
+use mofang_games::{MofangNode, SigmarNode};
+
+#[test]
+fn round_trip_fresh_games() {
+    let mofang = Game::new(MofangNode::new_game(7).unwrap());
+    let loaded: Game<MofangNode> = save::read(&save::write(&mofang)).unwrap();
+    assert_eq!(layout(mofang.board()), layout(loaded.board()));
+    assert_eq!(mofang.board().diameter(), loaded.board().diameter());
+
+    let sigmar = Game::new(SigmarNode::new_game(7).unwrap());
+    let loaded: Game<SigmarNode> = save::read(&save::write(&sigmar)).unwrap();
+    assert_eq!(layout(sigmar.board()), layout(loaded.board()));
+}
+
+#[test]
+fn round_trip_history_and_selection() {
+    let mut board = Board::new(7);
+    board
+        .set_node(Coordinate::new(-2, 0), Some(MofangNode::Yin))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(2, 0), Some(MofangNode::Yang))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(0, 2), Some(MofangNode::Fire))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(0, -2), Some(MofangNode::Metal))
+        .unwrap();
+    let mut game = Game::new(board);
+    // Yin + Yang -> Creation, Creation + Creation, then undo that last one
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    game.click(Coordinate::new(-2, 0));
+    game.click(Coordinate::new(2, 0));
+    game.undo();
+    game.click(Coordinate::new(0, 2));
+
+    let text = save::write(&game);
+    let mut loaded: Game<MofangNode> = save::read(&text).unwrap();
+    assert_eq!(save::write(&loaded), text);
+    assert_eq!(layout(game.board()), layout(loaded.board()));
+    assert_eq!(loaded.selected(), &[Coordinate::new(0, 2)]);
+    assert_eq!(loaded.history().len(), 1);
+    assert_eq!(
+        loaded.history()[0].after,
+        vec![Some(MofangNode::Creation), Some(MofangNode::Creation)]
+    );
+
+    // and the history still works after loading
+    assert!(loaded.redo());
+    assert!(loaded.undo());
+    assert!(loaded.undo());
+    assert_eq!(
+        loaded.board().get_node(Coordinate::new(2, 0)),
+        Some(&MofangNode::Yang)
+    );
+}
+
+#[test]
+fn loads_hand_written_saves() {
+    let text = "
+        mofang-save 1
+        # comments are fine
+        game mofang
+        diameter 5
+        node 0 0 qi
+        node 1 0 qi
+    ";
+    let mut game: Game<MofangNode> = save::read(text).unwrap();
+    game.click(Coordinate::new(0, 0));
+    game.click(Coordinate::new(1, 0));
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn rejects_bad_saves() {
+    let read = |text: &str| save::read::<MofangNode>(text).err().unwrap();
+
+    assert!(matches!(read("hello"), LoadError::NotASave));
+    assert!(matches!(
+        read("mofang-save 99\ngame mofang\ndiameter 5"),
+        LoadError::UnsupportedVersion(99)
+    ));
+    assert!(matches!(
+        read("mofang-save 1\ngame sigmar\ndiameter 5"),
+        LoadError::WrongGame(_)
+    ));
+    assert!(matches!(
+        read("mofang-save 1\ngame mofang\ndiameter 5\nnode 0 0 gold"),
+        LoadError::UnknownNode(_)
+    ));
+    assert!(matches!(
+        read("mofang-save 1\ngame mofang\ndiameter 5\nnode 9 9 qi"),
+        LoadError::OutOfBounds(_)
+    ));
+    assert!(matches!(
+        read("mofang-save 1\ngame mofang\ndiameter 5\nnode 0 qi"),
+        LoadError::Malformed { line: 4, .. }
+    ));
+}
+
+#[test]
+fn rejects_saves_that_dont_add_up() {
+    let read = |text: &str| save::read::<MofangNode>(text).err().unwrap();
+    let board = "mofang-save 5\ngame mofang\ndiameter 7\nnode -2 0 creation\nnode 2 0 creation";
+
+    // Fine as it is
+    let ok = format!("{}\nmove -2 0 yin creation 2 0 yang creation", board);
+    assert!(save::read::<MofangNode>(&ok).is_ok());
+
+    // The board doesn't have what the move left behind
+    assert!(matches!(
+        read(&format!("{}\nmove -2 0 fire - 2 0 metal -", board)),
+        LoadError::Inconsistent { line: 6 }
+    ));
+    // Yin and yin isn't a combo
+    assert!(matches!(
+        read(&format!(
+            "{}\nmove -2 0 yin creation 2 0 yin creation",
+            board
+        )),
+        LoadError::Inconsistent { line: 6 }
+    ));
+    // Nothing to select there
+    assert!(matches!(
+        read(&format!("{}\nselected 0 0", board)),
+        LoadError::Inconsistent { line: 6 }
+    ));
+    // Can't redo that from here
+    assert!(matches!(
+        read(&format!(
+            "{}\nundone -2 0 yin creation 2 0 yang creation",
+            board
+        )),
+        LoadError::Inconsistent { line: 6 }
+    ));
+
+    // A second diameter would throw the nodes away
+    assert!(matches!(
+        read(&format!("{}\ndiameter 7", board)),
+        LoadError::Malformed { line: 6, .. }
+    ));
+    assert!(matches!(
+        read("mofang-save 5\ngame mofang\ndiameter 2000000000"),
+        LoadError::Malformed { line: 3, .. }
+    ));
+    assert!(matches!(
+        read("mofang-save 5\ngame mofang\ndiameter -3"),
+        LoadError::Malformed { line: 3, .. }
+    ));
+}
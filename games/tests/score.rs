@@ -0,0 +1,233 @@
+mod common;
+
+use common::fire_and_metal;
+use hex2d::Coordinate;
+use mofang_engine::{
+    save, score::POINTS_PER_NODE, stats::Stats, Board, Game, Mask, Node, Solver, Status,
+};
+use mofang_games::{MofangNode, SigmarNode, DESTRUCTION_BONUS, QUINTESSENCE_BONUS};
+
+use std::time::Duration;
+
+/// The nodes spread out around the middle, so they're all free.
+fn spread_out<N: Node + Clone>(nodes: &[N]) -> Game<N> {
+    let spots = [(0, 0), (2, 0), (-2, 0), (0, 2), (0, -2), (2, -2)];
+    let nodes: Vec<_> = spots.iter().copied().zip(nodes.iter().cloned()).collect();
+    common::game_with(7, &nodes)
+}
+
+fn click_all<N: Node + Clone>(game: &mut Game<N>) {
+    let coords: Vec<_> = game.board().nodes_iter().map(|(c, _)| c).collect();
+    // Click the middle first, since that's where the Destruction or Quintessence is
+    for coord in Some(Coordinate::new(0, 0)).into_iter().chain(coords) {
+        if game.board().get_node(coord).is_some() && !game.selected().contains(&coord) {
+            game.click(coord);
+        }
+    }
+}
+
+#[test]
+fn cleared_nodes_score_points() {
+    let mut game = Game::new(fire_and_metal());
+    assert_eq!(game.score().points, 0);
+    click_all(&mut game);
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(game.score().points, 2 * POINTS_PER_NODE);
+}
+
+#[test]
+fn destruction_gets_a_bonus() {
+    let mut game = spread_out(&[
+        MofangNode::Destruction,
+        MofangNode::Wood,
+        MofangNode::Fire,
+        MofangNode::Earth,
+        MofangNode::Metal,
+        MofangNode::Water,
+    ]);
+    click_all(&mut game);
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(game.score().points, 6 * POINTS_PER_NODE + DESTRUCTION_BONUS);
+}
+
+#[test]
+fn quintessence_gets_a_bonus() {
+    let mut game = spread_out(&[
+        SigmarNode::Quintessence,
+        SigmarNode::Water,
+        SigmarNode::Earth,
+        SigmarNode::Fire,
+        SigmarNode::Air,
+    ]);
+    click_all(&mut game);
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(
+        game.score().points,
+        5 * POINTS_PER_NODE + QUINTESSENCE_BONUS
+    );
+}
+
+#[test]
+fn moves_count_undos_and_redos() {
+    let mut game = Game::new(fire_and_metal());
+    click_all(&mut game);
+    assert_eq!(game.moves(), 1);
+    game.undo();
+    // Undone points go away, but the move still happened
+    assert_eq!(game.score().points, 0);
+    assert_eq!(game.moves(), 1);
+    game.redo();
+    assert_eq!(game.moves(), 2);
+}
+
+#[test]
+fn the_clock_stops_when_the_game_ends() {
+    let mut game = Game::new(fire_and_metal());
+    game.add_time(Duration::from_secs(3));
+    click_all(&mut game);
+    game.add_time(Duration::from_secs(100));
+    assert_eq!(game.elapsed(), Duration::from_secs(3));
+
+    let loaded: Game<MofangNode> = save::read(&save::write(&game)).unwrap();
+    assert_eq!(loaded.score(), game.score());
+}
+
+#[test]
+fn stats_track_wins_and_streaks() {
+    let mut stats = Stats::new();
+    let mut won = Game::new(fire_and_metal());
+    won.add_time(Duration::from_secs(40));
+    click_all(&mut won);
+    let mut faster = Game::new(fire_and_metal());
+    faster.add_time(Duration::from_secs(25));
+    click_all(&mut faster);
+
+    stats.record(&won);
+    stats.record(&faster);
+    stats.record(&won);
+    let record = stats.get("mofang", &Mask::hexagon(7)).unwrap();
+    assert_eq!((record.played, record.won, record.streak), (3, 3, 3));
+    assert_eq!(record.best_time, Some(Duration::from_secs(25)));
+    assert_eq!(record.best_score, Some(2 * POINTS_PER_NODE));
+
+    // Giving up breaks the streak, but not the best one
+    stats.record(&Game::new(fire_and_metal()));
+    let record = stats.get("mofang", &Mask::hexagon(7)).unwrap();
+    assert_eq!((record.played, record.won, record.streak), (4, 3, 0));
+    assert_eq!(record.best_streak, 3);
+    assert!(stats.get("mofang", &Mask::hexagon(11)).is_none());
+    assert!(stats.get("sigmar", &Mask::hexagon(7)).is_none());
+}
+
+#[test]
+fn stats_round_trip() {
+    let mut stats = Stats::new();
+    let mut game = spread_out(&[SigmarNode::Water, SigmarNode::Water]);
+    game.add_time(Duration::from_millis(1234));
+    click_all(&mut game);
+    stats.record(&game);
+    stats.record(&Game::new(fire_and_metal()));
+
+    let text = stats.write();
+    let loaded = Stats::read(&text).unwrap();
+    assert_eq!(loaded, stats);
+    assert_eq!(loaded.records()[0].game, "mofang");
+    assert_eq!(loaded.records()[0].best_time, None);
+    assert_eq!(
+        loaded.records()[1].best_time,
+        Some(Duration::from_millis(1234))
+    );
+
+    assert!(Stats::read("mofang-save 3").is_err());
+    assert!(Stats::read("mofang-stats 1\nrecord mofang 11 1 1").is_err());
+}
+
+#[test]
+fn seeds_are_saved() {
+    let game = Game::seeded(MofangNode::new_game(42).unwrap(), 42);
+    let loaded: Game<MofangNode> = save::read(&save::write(&game)).unwrap();
+    assert_eq!(loaded.seed(), Some(42));
+    let unseeded: Game<MofangNode> =
+        save::read(&save::write(&Game::new(fire_and_metal()))).unwrap();
+    assert_eq!(unseeded.seed(), None);
+}
+
+#[test]
+fn stats_keep_bests_per_seed() {
+    let win = |seed, secs| {
+        let board = fire_and_metal();
+        let mut game = Game::seeded(board, seed);
+        game.add_time(Duration::from_secs(secs));
+        click_all(&mut game);
+        game
+    };
+    let mut stats = Stats::new();
+    stats.record(&win(5, 30));
+    let mut slow_but_tidy = win(5, 50);
+    // An extra undo and redo costs moves
+    slow_but_tidy.undo();
+    slow_but_tidy.redo();
+    stats.record(&slow_but_tidy);
+    stats.record(&win(6, 10));
+
+    let best = stats.best_on_seed("mofang", 5).unwrap();
+    assert_eq!(best.time, Duration::from_secs(30));
+    assert_eq!(best.moves, 1);
+    assert_eq!(
+        stats.best_on_seed("mofang", 6).unwrap().time,
+        Duration::from_secs(10)
+    );
+    assert!(stats.best_on_seed("sigmar", 5).is_none());
+    // Unseeded games don't get a seed best
+    stats.record(&Game::new(fire_and_metal()));
+    assert_eq!(stats.seed_bests().len(), 2);
+
+    assert_eq!(Stats::read(&stats.write()).unwrap(), stats);
+}
+
+#[test]
+fn assisted_wins_dont_set_bests() {
+    let mut helped = Game::seeded(fire_and_metal(), 5);
+    helped.add_time(Duration::from_secs(5));
+    assert!(helped.hint(&Solver::default()).is_some());
+    assert!(helped.assisted());
+    click_all(&mut helped);
+    assert_eq!(helped.status(), Status::Won);
+
+    let mut stats = Stats::new();
+    stats.record(&helped);
+    let record = stats.get("mofang", &Mask::hexagon(7)).unwrap();
+    assert_eq!((record.played, record.won, record.streak), (1, 1, 1));
+    assert_eq!((record.best_time, record.best_score), (None, None));
+    assert!(stats.best_on_seed("mofang", 5).is_none());
+
+    // It sticks through a save
+    let loaded: Game<MofangNode> = save::read(&save::write(&helped)).unwrap();
+    assert!(loaded.assisted());
+    assert!(!Game::new(fire_and_metal()).assisted());
+}
+
+#[test]
+fn stats_keep_shapes_apart() {
+    let mut stats = Stats::new();
+    stats.record(&Game::new(fire_and_metal()));
+    let dented = Mask::hexagon(7).without(Coordinate::new(-3, 0));
+    stats.record(&Game::new(Board::<MofangNode>::with_mask(dented.clone())));
+    stats.record(&Game::new(Board::<MofangNode>::with_mask(dented.clone())));
+
+    assert_eq!(stats.records().len(), 2);
+    assert_eq!(stats.get("mofang", &Mask::hexagon(7)).unwrap().played, 1);
+    assert_eq!(stats.get("mofang", &dented).unwrap().played, 2);
+    assert_eq!(Stats::read(&stats.write()).unwrap(), stats);
+
+    // Shape names don't depend on how the mask was made, or on the machine
+    assert_eq!(dented.shape_name(), "mask-04de2287");
+    let redrawn = Mask::from_coords(dented.coords().iter().rev().cloned());
+    assert_eq!(redrawn.shape_name(), dented.shape_name());
+    assert_ne!(dented.shape_name(), Mask::hexagon(7).shape_name());
+    assert_eq!(Mask::hexagon(7).shape_name(), "hexagon");
+
+    // Records from before shapes are hexagons
+    let old = Stats::read("mofang-stats 2\nrecord mofang 7 4 1 0 1 - -").unwrap();
+    assert_eq!(old.get("mofang", &Mask::hexagon(7)).unwrap().played, 4);
+}
@@ -0,0 +1,341 @@
+mod common;
+
+use common::layout;
+use hex2d::Coordinate;
+use mofang_engine::{generation::GENERATOR_VERSION, Board, Node};
+use mofang_games::{MofangNode, SigmarNode};
+
+const SEEDS: &[u64] = &[0, 1, 2, 42, 1337, 0xdead_beef, u64::MAX];
+
+/// Seeds and the boards they make, drawn by `picture`.
+type Golden = &'static [(u64, &'static [&'static str])];
+
+/// One letter per node and `.` for empty slots, one line per row of hexes.
+const CODES: &[(&str, char)] = &[
+    // mofang
+    ("wood", 'W'),
+    ("fire", 'F'),
+    ("earth", 'E'),
+    ("metal", 'M'),
+    ("water", 'A'),
+    ("heavenly", 'H'),
+    ("earthly", 'L'),
+    ("human", 'U'),
+    ("yin", 'Y'),
+    ("yang", 'G'),
+    ("creation", 'C'),
+    ("destruction", 'D'),
+    ("qi", 'Q'),
+    // sigmar
+    ("salt", 'S'),
+    ("quintessence", '5'),
+    ("air", 'R'),
+    ("quicksilver", 'K'),
+    ("lead", '1'),
+    ("tin", '2'),
+    ("iron", '3'),
+    ("copper", '4'),
+    ("silver", '6'),
+    ("gold", '7'),
+    ("vitae", 'V'),
+    ("mors", 'X'),
+];
+
+fn picture<N: Node>(board: &Board<N>) -> Vec<String> {
+    let mut slots: Vec<_> = board.nodes_iter().collect();
+    slots.sort_by_key(|(coord, _)| (coord.y, coord.x));
+    let mut rows: Vec<(i32, String)> = Vec::new();
+    for (coord, node) in slots {
+        let code = match node {
+            Some(node) => {
+                CODES
+                    .iter()
+                    .find(|(name, _)| *name == node.texture_name())
+                    .expect("every node has a code")
+                    .1
+            }
+            None => '.',
+        };
+        match rows.last_mut() {
+            Some((y, row)) if *y == coord.y => row.push(code),
+            _ => rows.push((coord.y, code.to_string())),
+        }
+    }
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+fn check_golden<N: Node>(golden: Golden, generate: impl Fn(u64) -> Board<N>) {
+    for (seed, expected) in golden {
+        assert_eq!(
+            picture(&generate(*seed)),
+            *expected,
+            "{} seed {} made a different board",
+            N::name(),
+            seed
+        );
+    }
+}
+
+#[test]
+fn mofang_same_seed_same_board() {
+    for &seed in SEEDS {
+        assert_eq!(
+            layout(&MofangNode::new_game(seed).unwrap()),
+            layout(&MofangNode::new_game(seed).unwrap()),
+            "seed {} produced two different boards",
+            seed
+        );
+    }
+}
+
+#[test]
+fn sigmar_same_seed_same_board() {
+    for &seed in SEEDS {
+        assert_eq!(
+            layout(&SigmarNode::new_game(seed).unwrap()),
+            layout(&SigmarNode::new_game(seed).unwrap()),
+            "seed {} produced two different boards",
+            seed
+        );
+    }
+}
+
+#[test]
+fn different_seeds_different_boards() {
+    let mofang = SEEDS
+        .iter()
+        .map(|&seed| layout(&MofangNode::new_game(seed).unwrap()))
+        .collect::<Vec<_>>();
+    let sigmar = SEEDS
+        .iter()
+        .map(|&seed| layout(&SigmarNode::new_game(seed).unwrap()))
+        .collect::<Vec<_>>();
+    for i in 0..SEEDS.len() {
+        for j in i + 1..SEEDS.len() {
+            assert_ne!(mofang[i], mofang[j], "seeds {} and {}", SEEDS[i], SEEDS[j]);
+            assert_ne!(sigmar[i], sigmar[j], "seeds {} and {}", SEEDS[i], SEEDS[j]);
+        }
+    }
+}
+
+#[test]
+fn known_seeds_place_the_whole_bank() {
+    for &seed in SEEDS {
+        let mofang = MofangNode::new_game(seed).unwrap();
+        // 52 from the bank, plus the Destruction and 3 Qi around the center
+        assert_eq!(mofang.node_count(), 56, "mofang seed {}", seed);
+        assert_eq!(
+            mofang.get_node(Coordinate::new(0, 0)),
+            Some(&MofangNode::Destruction)
+        );
+
+        let sigmar = SigmarNode::new_game(seed).unwrap();
+        // 46 from the bank, plus the Gold in the center
+        assert_eq!(sigmar.node_count(), 47, "sigmar seed {}", seed);
+        assert_eq!(
+            sigmar.get_node(Coordinate::new(0, 0)),
+            Some(&SigmarNode::Gold)
+        );
+    }
+}
+
+// If one of these fails, every shared seed, daily challenge and replay for that game
+// now means a different board.
+// Only update them on purpose, and bump `GENERATOR_VERSION` when you do.
+//
+// These were pinned after the fact, but every commit back to seeded generation
+// makes these same boards (the solvable ones back to when the solver came in),
+// so nothing since then has changed what a seed means.
+
+#[test]
+fn layouts_are_from_this_generator() {
+    assert_eq!(GENERATOR_VERSION, 1);
+}
+
+#[test]
+fn known_seeds_make_known_boards() {
+    check_golden(MOFANG_LAYOUTS, |seed| MofangNode::new_game(seed).unwrap());
+    check_golden(SIGMAR_LAYOUTS, |seed| SigmarNode::new_game(seed).unwrap());
+}
+
+#[test]
+fn known_seeds_make_known_solvable_boards() {
+    check_golden(MOFANG_SOLVABLE_LAYOUTS, |seed| {
+        MofangNode::new_solvable_game(seed).unwrap()
+    });
+    check_golden(SIGMAR_SOLVABLE_LAYOUTS, |seed| {
+        SigmarNode::new_solvable_game(seed).unwrap()
+    });
+}
+
+const MOFANG_LAYOUTS: Golden = &[
+    (
+        0,
+        &[
+            "....WA",
+            ".....EH",
+            "W.LEAM.F",
+            "A.M..FE.A",
+            "E.G.QF.F.Q",
+            "MUUMADQ.L..",
+            "A.F.QH.Y..",
+            "U.W.HAM.U",
+            "F.FHWQWW",
+            "E....MQ",
+            "EMWLEL",
+        ],
+    ),
+    (
+        42,
+        &[
+            "EMQWFA",
+            "M....UF",
+            "L...FM.W",
+            "F.A..QA.W",
+            "F.E.QH.W.Q",
+            "EAWMADQ.W.E",
+            "L.L.QM.L..",
+            "A.H..WF..",
+            "E.MFEU.H",
+            "Y....MU",
+            ".GAHEU",
+        ],
+    ),
+    (
+        0xdead_beef,
+        &[
+            "LMAFWA",
+            "W....WF",
+            "F.UHWL.E",
+            "F.E..WM.E",
+            "E.U.QM.Q..",
+            "MAAMEDQ.F..",
+            "Q.F.QA.F..",
+            "H.U..EH.Q",
+            "E.WHAY.W",
+            ".....LM",
+            ".LUMAG",
+        ],
+    ),
+];
+
+const SIGMAR_LAYOUTS: Golden = &[
+    (
+        0,
+        &[
+            "..AAA6",
+            ".....SK",
+            "..FFE2.S",
+            ".....FR.A",
+            "F.4.RA.E..",
+            "S3EAS7K.E..",
+            "E.R.RE.F..",
+            "E.F..FK..",
+            "K.RRRR.E",
+            ".....1F",
+            "...KAA",
+        ],
+    ),
+    (
+        42,
+        &[
+            "FF....",
+            "KF.....",
+            "1.ERK3..",
+            "K.A4..E.F",
+            "F.E.S6.R.R",
+            "R...F7AKARK",
+            "..E.EE...E",
+            "..RR..E..",
+            "S.RA2F..",
+            "FS.....",
+            "AAASA.",
+        ],
+    ),
+    (
+        0xdead_beef,
+        &[
+            "K...AA",
+            "F....R.",
+            "S.FF14..",
+            "K.A..3R..",
+            "R.K.FS.R..",
+            "FEE2E7R.A..",
+            "..E.AS.R..",
+            "..E..RK.S",
+            "..FEA6.A",
+            ".....FR",
+            ".FKAEE",
+        ],
+    ),
+];
+
+const MOFANG_SOLVABLE_LAYOUTS: Golden = &[
+    (
+        0,
+        &[
+            "AUFAMH",
+            "F....WQ",
+            "..E.YA..",
+            "..M..QM.A",
+            "..A.QE.E..",
+            "FHQWGDQ.W..",
+            "E.U.QF.M.E",
+            "L.A..E..M",
+            "E.MFWM.F",
+            "F....AW",
+            "UWWU.L",
+        ],
+    ),
+    (
+        42,
+        &[
+            "EMQWFA",
+            "M....UF",
+            "L...FM.W",
+            "F.A..QA.W",
+            "F.E.QH.W.Q",
+            "EAWMA.Q.W.E",
+            "L.L.QM.L..",
+            "A.H..W...",
+            "E.MFEU.H",
+            "Y....MU",
+            ".GAHEU",
+        ],
+    ),
+];
+
+const SIGMAR_SOLVABLE_LAYOUTS: Golden = &[
+    (
+        0,
+        &[
+            "...AKA",
+            ".....AF",
+            "..E3RR.F",
+            "4.K..SS..",
+            "6.E.1A.A..",
+            "EKRKF7E.E..",
+            "R.E.FA.A..",
+            "F.F..AR.K",
+            "S.RFEE.S",
+            ".....2R",
+            "....RF",
+        ],
+    ),
+    (
+        42,
+        &[
+            "SFA...",
+            "FA.....",
+            "R.EEAA..",
+            "A.AE..E.R",
+            "..A.K2.4.S",
+            "..E.R7SRKAR",
+            "..R.6K.3.F",
+            "..KF..S.R",
+            "E.FEFE..",
+            "RK.....",
+            "1FF...",
+        ],
+    ),
+];
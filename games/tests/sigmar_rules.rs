@@ -0,0 +1,25 @@
+use hex2d::Coordinate;
+use mofang_engine::{Board, Click, Game, Node, PartialResult, Status};
+use mofang_games::SigmarNode;
+
+#[test]
+fn gold_goes_away_on_its_own() {
+    assert!(matches!(
+        SigmarNode::select(&[&SigmarNode::Gold], &()),
+        PartialResult::Success(change) if change == vec![None]
+    ));
+    // The other metals still need quicksilver
+    assert!(matches!(
+        SigmarNode::select(&[&SigmarNode::Silver], &()),
+        PartialResult::Continue
+    ));
+
+    // Without this the gold in the center of every board could never be cleared
+    let board = Board::builder(5)
+        .nodes(vec![(Coordinate::new(0, 0), SigmarNode::Gold)])
+        .build()
+        .unwrap();
+    let mut game = Game::new(board);
+    assert_eq!(game.click(Coordinate::new(0, 0)), Click::Matched);
+    assert_eq!(game.status(), Status::Won);
+}
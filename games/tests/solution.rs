@@ -0,0 +1,66 @@
+mod common;
+
+use hex2d::Coordinate;
+use mofang_engine::{save::LoadError, solution, Board, Solver};
+use mofang_games::{MofangNode, SigmarNode};
+
+fn yin_and_yang() -> Board<MofangNode> {
+    common::board_with(7, &[((-2, 0), MofangNode::Yin), ((2, 0), MofangNode::Yang)])
+}
+
+#[test]
+fn steps_say_what_each_combo_makes() {
+    let line = vec![
+        vec![Coordinate::new(-2, 0), Coordinate::new(2, 0)],
+        vec![Coordinate::new(2, 0), Coordinate::new(-2, 0)],
+    ];
+    let steps = solution::steps(&yin_and_yang(), &line).unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0].coords, line[0]);
+    assert_eq!(steps[0].before, vec![MofangNode::Yin, MofangNode::Yang]);
+    assert_eq!(
+        steps[0].after,
+        vec![Some(MofangNode::Creation), Some(MofangNode::Creation)]
+    );
+    assert_eq!(steps[1].before, vec![MofangNode::Creation; 2]);
+    assert_eq!(steps[1].after, vec![None, None]);
+    assert_eq!(
+        solution::describe(&steps[0]),
+        "yin (-2, 0) + yang (2, 0) -> creation (-2, 0), creation (2, 0)"
+    );
+}
+
+#[test]
+fn bad_lines_say_where_they_went_wrong() {
+    let yin_yang = vec![Coordinate::new(-2, 0), Coordinate::new(2, 0)];
+    // The second combo isn't done yet
+    let line = vec![yin_yang.clone(), vec![Coordinate::new(2, 0)]];
+    assert_eq!(solution::steps(&yin_and_yang(), &line), Err(1));
+    // There's nothing there
+    let line = vec![vec![Coordinate::new(0, 0)], yin_yang];
+    assert_eq!(solution::steps(&yin_and_yang(), &line), Err(0));
+}
+
+#[test]
+fn solver_lines_round_trip() {
+    let (board, line) = Solver::default().solvable_game::<MofangNode>(4).unwrap();
+    let steps = solution::steps(&board, &line).unwrap();
+    assert_eq!(steps.len(), line.len());
+
+    let text = solution::write(&steps);
+    assert!(text.starts_with("mofang-solution 1\ngame mofang\ncombo "));
+    assert_eq!(solution::read::<MofangNode>(&text).unwrap(), steps);
+
+    assert!(matches!(
+        solution::read::<SigmarNode>(&text),
+        Err(LoadError::WrongGame(_))
+    ));
+    assert!(matches!(
+        solution::read::<MofangNode>("mofang-solution 1\ngame mofang\ncombo 0 0 fire"),
+        Err(LoadError::Malformed { line: 3, .. })
+    ));
+    assert!(matches!(
+        solution::read::<MofangNode>("mofang-solution 1\ngame mofang\ncombo 0 0 fire smoke"),
+        Err(LoadError::UnknownNode(_))
+    ));
+}
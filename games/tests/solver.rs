@@ -0,0 +1,135 @@
+use hex2d::Coordinate;
+use mofang_engine::{
+    solver::{any_legal_combo, legal_combos},
+    Board, Node, PartialResult, Solution, Solver,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+use std::hash::Hash;
+
+/// Click through the line like a player would and make sure it clears the board.
+fn assert_clears<N: Node + Clone>(mut board: Board<N>, line: &[Vec<Coordinate>]) {
+    for combo in line {
+        for (idx, coord) in combo.iter().enumerate() {
+            let node = board.get_node(*coord).expect("clicked an empty slot");
+            assert!(node.can_select(&board, coord, &combo[..idx]));
+        }
+        let nodes: Vec<_> = combo.iter().flat_map(|c| board.get_node(*c)).collect();
+        let change = match N::select(&nodes, board.rules()) {
+            PartialResult::Success(change) => change,
+            _ => panic!("combo at {:?} isn't a match", combo),
+        };
+        for (&coord, node) in combo.iter().zip(change) {
+            board.set_node(coord, node).unwrap();
+        }
+    }
+    assert!(board.nodes_iter().all(|(_, node)| node.is_none()));
+}
+
+fn check_solvable_game<N: Node + Clone + Hash>(seed: u64) {
+    let (board, line) = Solver::default().solvable_game::<N>(seed).unwrap();
+    assert_clears(board, &line);
+}
+
+#[test]
+fn mofang_solvable_games_clear() {
+    check_solvable_game::<MofangNode>(3);
+    check_solvable_game::<MofangNode>(4);
+}
+
+#[test]
+fn sigmar_solvable_games_clear() {
+    check_solvable_game::<SigmarNode>(1);
+    check_solvable_game::<SigmarNode>(2);
+}
+
+#[test]
+fn empty_board_is_solved() {
+    let board = Board::<MofangNode>::new(5);
+    assert!(legal_combos(&board).is_empty());
+    assert!(!any_legal_combo(&board));
+    assert!(Solver::default().solve(&board).is_solved());
+}
+
+#[test]
+fn lone_node_is_impossible() {
+    let mut board = Board::new(5);
+    board
+        .set_node(Coordinate::new(0, 0), Some(MofangNode::Fire))
+        .unwrap();
+    assert!(legal_combos(&board).is_empty());
+    assert!(!any_legal_combo(&board));
+    assert!(matches!(
+        Solver::default().solve(&board),
+        Solution::Impossible
+    ));
+}
+
+#[test]
+fn lone_gold_is_solved() {
+    let mut board = Board::new(5);
+    board
+        .set_node(Coordinate::new(0, 0), Some(SigmarNode::Gold))
+        .unwrap();
+    assert!(Solver::default().solve(&board).is_solved());
+}
+
+#[test]
+fn any_legal_combo_agrees_with_the_list() {
+    for seed in 0..5 {
+        let board = MofangNode::new_game(seed).unwrap();
+        assert_eq!(any_legal_combo(&board), !legal_combos(&board).is_empty());
+        let board = SigmarNode::new_game(seed).unwrap();
+        assert_eq!(any_legal_combo(&board), !legal_combos(&board).is_empty());
+    }
+    assert!(any_legal_combo(&salt_trap()));
+}
+
+/// Two Water, a Fire and a Salt: the Salt has to go with the Fire.
+fn salt_trap() -> Board<SigmarNode> {
+    let mut board = Board::new(7);
+    board
+        .set_node(Coordinate::new(-3, 0), Some(SigmarNode::Water))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(3, 0), Some(SigmarNode::Water))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(0, -3), Some(SigmarNode::Fire))
+        .unwrap();
+    board
+        .set_node(Coordinate::new(0, 3), Some(SigmarNode::Salt))
+        .unwrap();
+    board
+}
+
+/// Does this combo waste the Salt on a Water?
+fn wastes_salt(board: &Board<SigmarNode>, combo: &[Coordinate]) -> bool {
+    let mut nodes: Vec<_> = combo.iter().flat_map(|c| board.get_node(*c)).collect();
+    nodes.sort();
+    nodes == vec![&SigmarNode::Salt, &SigmarNode::Water]
+}
+
+#[test]
+fn dead_ends_rank_last() {
+    let board = salt_trap();
+    let hints = Solver::default().rank_combos(&board);
+    assert_eq!(hints.len(), legal_combos(&board).len());
+    assert!(hints[0].outlook.is_solved());
+
+    for hint in hints {
+        assert_eq!(
+            matches!(hint.outlook, Solution::Impossible),
+            wastes_salt(&board, &hint.combo)
+        );
+    }
+}
+
+#[test]
+fn hints_keep_the_board_solvable() {
+    let board = salt_trap();
+    let hint = Solver::default().hint(&board).unwrap();
+    assert!(!wastes_salt(&board, &hint));
+
+    assert_eq!(Solver::default().hint(&Board::<SigmarNode>::new(5)), None);
+}
@@ -0,0 +1,88 @@
+use hex2d::Coordinate;
+use mofang_engine::{
+    unmatch::{self, UnmatchError},
+    Board, Game, GenOptions, Node, Status,
+};
+use mofang_games::{MofangNode, SigmarNode};
+
+use std::hash::Hash;
+
+/// Click through the line in a real game and make sure it's won at the end.
+fn assert_wins<N: Node + Clone + Hash + Eq>(board: Board<N>, line: &[Vec<Coordinate>]) {
+    let mut game = Game::new(board);
+    for combo in line {
+        for &coord in combo {
+            assert!(game.is_selectable(coord), "can't click {:?}", coord);
+            game.click(coord);
+        }
+        assert!(game.selected().is_empty(), "{:?} didn't match", combo);
+    }
+    assert_eq!(game.status(), Status::Won);
+}
+
+fn check_certified_games<N: Node + Clone + Hash + Eq>() {
+    let options = N::gen_options();
+    for seed in 0..5 {
+        let (board, line) = N::new_certified_game(seed);
+        assert_eq!(board.node_count(), options.node_count(), "seed {}", seed);
+        if let Some(center) = &options.center {
+            assert!(board.get_node(Coordinate::new(0, 0)) == Some(center));
+        }
+        assert_wins(board, &line);
+    }
+}
+
+#[test]
+fn mofang_certified_games_clear() {
+    check_certified_games::<MofangNode>();
+}
+
+#[test]
+fn sigmar_certified_games_clear() {
+    check_certified_games::<SigmarNode>();
+}
+
+#[test]
+fn certified_games_are_seeded() {
+    let (a, a_line) = SigmarNode::new_certified_game(11);
+    let (b, b_line) = SigmarNode::new_certified_game(11);
+    assert_eq!(a_line, b_line);
+    assert!(a.nodes_iter().eq(b.nodes_iter()));
+}
+
+#[test]
+fn creation_gets_unmade() {
+    // Yin and Yang only go away by making Creation, which then has to go somewhere
+    let options = GenOptions {
+        diameter: 7,
+        center: None,
+        around_center: Vec::new(),
+        bank: vec![
+            (MofangNode::Yin, 2),
+            (MofangNode::Yang, 2),
+            (MofangNode::Wood, 1),
+            (MofangNode::Earth, 1),
+        ],
+        ..MofangNode::gen_options()
+    };
+    for seed in 0..10 {
+        let (board, line) = unmatch::generate(seed, &options).unwrap();
+        assert_eq!(board.count(&MofangNode::Yin), 2);
+        assert_eq!(board.count(&MofangNode::Creation), 0);
+        assert_wins(board, &line);
+    }
+}
+
+#[test]
+fn unpairable_banks_are_rejected() {
+    // Vitae only goes away with Mors
+    let options = GenOptions {
+        center: None,
+        bank: vec![(SigmarNode::Salt, 2), (SigmarNode::Vitae, 1)],
+        ..SigmarNode::gen_options()
+    };
+    assert_eq!(
+        unmatch::generate(0, &options).err(),
+        Some(UnmatchError::Unpairable)
+    );
+}